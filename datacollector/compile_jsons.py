@@ -29,6 +29,31 @@ def single_file():
                 output[obj_name] = obj
         stream.write(json.dumps(output))
 
+# Not on pokemondb pages, see Category in poke-rater-core/src/data.rs
+CATEGORIES = {
+    "Legendary": [144, 145, 146, 150, 243, 244, 245, 249, 250, 377, 378, 379, 380, 381, 382, 383, 384,
+                  480, 481, 482, 483, 484, 485, 486, 487, 488, 638, 639, 640, 641, 642, 643, 644, 645, 646,
+                  716, 717, 718, 772, 773, 785, 786, 787, 788, 789, 790, 791, 792, 800,
+                  888, 889, 890, 891, 892, 894, 895, 896, 897, 898, 905, 1001, 1002, 1003, 1004, 1007, 1008],
+    "Mythical": [151, 251, 385, 386, 489, 490, 491, 492, 493, 494, 647, 648, 649, 719, 720, 721,
+                 801, 802, 807, 808, 809, 893],
+    # Whole lines
+    "Starter": [*range(1, 10), *range(152, 161), *range(252, 261), *range(387, 396), *range(495, 504),
+                *range(650, 659), *range(722, 731), *range(810, 819), *range(906, 915)],
+    # Final evolutions with 600 base stats
+    "PseudoLegendary": [149, 248, 373, 376, 445, 635, 706, 784, 887, 998],
+    "Paradox": [*range(984, 996), 1005, 1006, 1009, 1010],
+    "UltraBeast": [*range(793, 800), 803, 804, 805, 806],
+    # Revived from fossils, whole lines
+    "Fossil": [*range(138, 143), *range(345, 349), *range(408, 412), *range(564, 568), *range(696, 700), *range(880, 884)],
+    "Baby": [172, 173, 174, 175, 236, 238, 239, 240, 298, 360, 406, 433, 438, 439, 440, 446, 447, 458, 848],
+}
+
+def add_categories(pokemon: dict):
+    '''Add category list, in the order of CATEGORIES'''
+    pokemon['categories'] = [name for name, numbers in CATEGORIES.items() if pokemon['dex_no'] in numbers]
+    return pokemon
+
 def clean_data(pokemon: dict):
     '''Fix mistakes inside json files'''
     # Missing Id num
@@ -60,7 +85,7 @@ def clean_data(pokemon: dict):
     for grade in pokemon['matchups']:
         grade = [*set(grade)]
 
-    return pokemon
+    return add_categories(pokemon)
 
 def compile_slides():
     '''Compile jsons with slides'''
@@ -0,0 +1,287 @@
+use crate::data::*;
+use crate::pokemon::*;
+use crate::regression::{Feature, feature_importance, surprising_grades};
+use crate::typechart::stab_multiplier;
+use crate::evolution::{evolution_preference, family_consistency};
+use std::{iter::zip, collections::HashMap};
+use strum::IntoEnumIterator;
+
+pub fn run_analysis(list: &[Pokemon], num_grades: i32) -> AnalysisOutput {
+    //! num_grades is total number of discrete grades, at least 1 (checked by Dex::analyze)
+    /*
+     * Generation: avg-grade/gen
+     * Typing: avg-grade/type
+     * Dual/Single types : Ratio(avg-grade/dual:avg-grade/single)
+     * Anime/Manga: avg-#appearances/grade
+     * Number of evolutions: avg-#evo/grade
+     * Color: avg-grade/color
+     * Category: avg-grade/category (Legendary, Starter, ...)
+     * Perfect scores: list of names&dex_no of pokemon with max score
+     * Worst scores: see perfect scores
+     * Distributions: histogram, median, sd, 95% CI of mean overall and per gen/type/color/category/#types
+     * Type matrix: avg-grade/(primary type, secondary type), and distributions per primary/secondary type
+     * Evolution stage: distributions per stage and fully evolved/not, preference within families, see evolution.rs
+     * Family consistency: grade spread within each family, most split and uniform families, trend from first to last stage
+     * Cross-tabs: avg-grade/(gen, type) and avg-grade/(gen, color), best and worst combinations
+     * Normalised scores: per gen/type/color/category/#types averages as 0-1 and percentile rank
+     * Feature importance: ridge regression of grade on all of the above, see regression.rs
+     * Surprising grades: largest leave-one-out residuals of that regression
+     * 
+     * ----------Uses AvgValuePerGrade struct----------
+     * Stats
+     *  - avg-stat-num/stat-name/grade
+     * Weaknesses 
+     *  - avg-matchup/type/grade
+     * STAB coverage
+     *  - %-super-effective/type/grade
+     * Matchup summary: avg weaknesses, resistances, immunities and STAB coverage per grade
+     * 
+     * ----------Unimplemented in .json----------
+     * Number of forms: avg-#forms/grade
+     */
+
+    let mut coverage = Coverage::new();
+
+    // {Name} ({dexno})
+    let mut perfect_scores: Vec<String> = Vec::new();
+    let mut worst_scores: Vec<String> = Vec::new();
+
+    // Count: #pokemon w/ trait, total: sum(grades)
+    let mut gen_count = [0.0; GEN_COUNT];
+    let mut gen_totals = [0.0; GEN_COUNT];
+
+    let mut typing_data: HashMap<PTypes, (f64, f64)> = HashMap::new();
+    let mut color_data: HashMap<PColors, (f64, f64)> = HashMap::new();
+
+    // (dual-total, dual-count, single-total, single-count)
+    let mut single_type_total = 0.0;   
+    let mut single_type_count = 0.0;
+    let mut dual_type_total = 0.0;   
+    let mut dual_type_count = 0.0;    
+    let mut manga_totals = vec![0.0; num_grades as usize];
+    let mut manga_count = vec![0.0; num_grades as usize];
+    let mut anime_totals = vec![0.0; num_grades as usize];
+    let mut anime_count = vec![0.0; num_grades as usize];
+    let mut evolutions_totals = vec![0.0; num_grades as usize];
+    let mut evolutions_count = vec![0.0; num_grades as usize];
+
+    // vec of hashmaps where key = StatName|PType, value = (total, count)
+    let mut stats_data: AvgValuePerGrade<StatNames> = AvgValuePerGrade::new(num_grades as usize);
+    let mut matchup_data: AvgValuePerGrade<PTypes> = AvgValuePerGrade::new(num_grades as usize);
+    let mut stab_coverage_data: AvgValuePerGrade<PTypes> = AvgValuePerGrade::new(num_grades as usize);
+    let mut matchup_summary = vec![MatchupSummary::default(); num_grades as usize];
+    // json data omits neutral matchups. 
+    // All types not included in pokemon.matchup must add 100
+    let mut typing_list: Vec<PTypes>;
+
+    // Histograms of grades, turned into distributions at the end
+    let empty = vec![0usize; num_grades as usize];
+    let mut overall_hist = empty.clone();
+    let mut gen_hist = vec![empty.clone(); GEN_COUNT];
+    let mut typing_hist: Vec<(PTypes, Vec<usize>)> = PTypes::iter().map(|x| (x, empty.clone())).collect();
+    let mut color_hist: Vec<(PColors, Vec<usize>)> = PColors::iter().map(|x| (x, empty.clone())).collect();
+    let mut category_hist: Vec<(Category, Vec<usize>)> = Category::iter().map(|x| (x, empty.clone())).collect();
+    let mut single_type_hist = empty.clone();
+    let mut dual_type_hist = empty.clone();
+    let mut primary_type_hist: Vec<(PTypes, Vec<usize>)> = PTypes::iter().map(|x| (x, empty.clone())).collect();
+    let mut secondary_type_hist: Vec<(PTypes, Vec<usize>)> = PTypes::iter().map(|x| (x, empty.clone())).collect();
+    let mut type_matrix = TypeMatrix::new();
+    let mut stage_hist: Vec<(EvolutionStage, Vec<usize>)> = EvolutionStage::iter().map(|x| (x, empty.clone())).collect();
+    let mut fully_evolved_hist = empty.clone();
+    let mut evolving_hist = empty.clone();
+    let mut gen_typing: CrossTab<PTypes> = CrossTab::new(PTypes::iter().collect());
+    let mut gen_color: CrossTab<PColors> = CrossTab::new(PColors::iter().collect());
+
+    let mut grade; 
+    let mut gen_no: usize;
+    for pokemon in list.iter() {
+        // Grades outside 1..=num_grades cannot be placed, so are treated as unrated
+        grade = match pokemon.grade {
+            Some(g) if g >= 1 && g <= num_grades => (g - 1) as f64,
+            _ => {
+                coverage.add(pokemon, false);
+                continue;
+            }
+        };
+        coverage.add(pokemon, true);
+
+        // pokemon with a perfect grade
+        if grade == (num_grades - 1) as f64 {
+            perfect_scores.push(String::from(&pokemon.name))
+        }
+        else if grade == 0.0 {
+            worst_scores.push(String::from(&pokemon.name))
+        }
+
+        let g = grade as usize;
+        overall_hist[g] += 1;
+        gen_hist[pokemon.gen_no - 1][g] += 1;
+        for hist in typing_hist.iter_mut().filter(|x| pokemon.is_typing(&x.0)) {
+            hist.1[g] += 1;
+        }
+        for hist in color_hist.iter_mut().filter(|x| x.0 == pokemon.color) {
+            hist.1[g] += 1;
+        }
+        for hist in category_hist.iter_mut().filter(|x| pokemon.is_category(&x.0)) {
+            hist.1[g] += 1;
+        }
+        if pokemon.typing.len() == 1 { single_type_hist[g] += 1 } else { dual_type_hist[g] += 1 }
+        for (position, hist) in [&mut primary_type_hist, &mut secondary_type_hist].into_iter().enumerate() {
+            if let Some(typing) = pokemon.typing.get(position) {
+                hist.iter_mut().find(|x| x.0 == *typing).unwrap().1[g] += 1;
+            }
+        }
+        type_matrix.add(&pokemon.typing, grade);
+        stage_hist.iter_mut().find(|x| x.0 == pokemon.stage).unwrap().1[g] += 1;
+        if pokemon.fully_evolved { fully_evolved_hist[g] += 1 } else { evolving_hist[g] += 1 }
+        for typing in &pokemon.typing {
+            gen_typing.add(pokemon.gen_no, typing, grade);
+        }
+        gen_color.add(pokemon.gen_no, &pokemon.color, grade);
+
+        // avg-grade/generation
+        gen_no = pokemon.gen_no - 1; 
+        gen_totals[gen_no] += grade;
+        gen_count[gen_no] += 1.0;
+        
+        // avg-grade/type
+        for typing in pokemon.typing.iter() {
+            if typing_data.contains_key(typing) {
+                typing_data.get_mut(typing).unwrap().0 += grade;
+                typing_data.get_mut(typing).unwrap().1 += 1.0;
+            } else {
+                typing_data.insert(*typing, (grade, 1.0));
+            }
+        }
+
+        // dual vs single
+        if pokemon.typing.len() == 1 {
+            single_type_total += grade;
+            single_type_count += 1.0;
+        } else {
+            dual_type_total += grade;
+            dual_type_count += 1.0;
+        } 
+        // println!("{} {}", pokemon.name, pokemon.dex_no);
+
+        // avg-#manga/grade
+        manga_totals[grade as usize] += pokemon.manga_count as f64;
+        manga_count[grade as usize] += 1.0;
+
+        // avg-#anime/grade
+        anime_totals[grade as usize] += pokemon.anime_count as f64;
+        anime_count[grade as usize] += 1.0;
+
+        // avg-#evo/grade
+        evolutions_totals[grade as usize] += pokemon.evolutions as f64;
+        evolutions_count[grade as usize] += 1.0;
+
+        //avg-grade/color
+        
+        let color = color_data.entry(pokemon.color).or_insert((0.0, 0.0));
+        color.0 += grade;
+        color.1 += 1.0;
+
+        // avg-stat-num/stat-name/grade
+        for stat in &pokemon.stats {
+            stats_data.add_value(grade as usize, stat.0, stat.1 as f64);
+        }
+
+        // avg-matchup/type/grade
+        typing_list = PTypes::iter().collect();
+        for matchup in &pokemon.matchups {
+            for typing in matchup.1 {
+                matchup_data.add_value(grade as usize, *typing, *matchup.0 as f64);
+
+                
+                typing_list.remove(typing_list.iter().position(|x| x == typing).unwrap());
+            }
+        }
+        // Add neutral matchups
+        for matchup in typing_list {
+            matchup_data.add_value(grade as usize, matchup, 100.0);
+        }
+
+        // %-super-effective/type/grade
+        for defend in PTypes::iter() {
+            let hits = stab_multiplier(&pokemon.typing, &[defend]) > 1.0;
+            stab_coverage_data.add_value(grade as usize, defend, if hits { 100.0 } else { 0.0 });
+        }
+        matchup_summary[grade as usize].add(pokemon);
+    }
+
+    // Calculate and Sort outputs
+    let mut typing_output: Vec<(PTypes, f64)> = typing_data.into_iter().map(|x| (x.0, x.1.0 / x.1.1)).collect();
+    typing_output.sort_by(|x, y| y.1.partial_cmp(&x.1).unwrap());
+
+    let mut color_output: Vec<(PColors, f64)> = color_data.into_iter().map(|x| (x.0, x.1.0 / x.1.1)).collect();
+    color_output.sort_by(|x, y| y.1.partial_cmp(&x.1).unwrap());
+
+    let gen_distribution: Vec<Distribution> = gen_hist.into_iter().map(Distribution::from_histogram).collect();
+    let typing_distribution: Vec<(PTypes, Distribution)> = typing_hist.into_iter().map(|x| (x.0, Distribution::from_histogram(x.1))).collect();
+    let color_distribution: Vec<(PColors, Distribution)> = color_hist.into_iter().map(|x| (x.0, Distribution::from_histogram(x.1))).collect();
+    let category_distribution: Vec<(Category, Distribution)> = category_hist.into_iter().map(|x| (x.0, Distribution::from_histogram(x.1))).collect();
+    let mut category_output: Vec<(Category, f64)> = category_distribution.iter().filter(|x| x.1.count > 0).map(|x| (x.0, x.1.mean)).collect();
+    category_output.sort_by(|x, y| y.1.total_cmp(&x.1));
+    let single_type_distribution = Distribution::from_histogram(single_type_hist);
+    let dual_type_distribution = Distribution::from_histogram(dual_type_hist);
+    let stage_distribution: Vec<(EvolutionStage, Distribution)> = stage_hist.into_iter().map(|x| (x.0, Distribution::from_histogram(x.1))).collect();
+    let percentiles = grade_percentiles(&overall_hist);
+
+    let (gen_typing, gen_color) = (gen_typing.get_result(), gen_color.get_result());
+    let mut combinations = gen_typing.combinations(MIN_COMBINATION_COUNT, |x| Feature::Typing(*x));
+    combinations.extend(gen_color.combinations(MIN_COMBINATION_COUNT, |x| Feature::Color(*x)));
+    combinations.sort_by(|x, y| y.average.total_cmp(&x.average));
+    let overall_distribution = Distribution::from_histogram(overall_hist);
+    let overall_mean = overall_distribution.mean;
+    let best_combinations = combinations.iter().take(COMBINATION_COUNT).filter(|x| x.average > overall_mean).cloned().collect();
+    let worst_combinations = combinations.iter().rev().take(COMBINATION_COUNT).filter(|x| x.average < overall_mean).cloned().collect();
+    let normalised = |x: &Distribution| NormalisedScore::new(x, &percentiles);
+
+    return AnalysisOutput {
+        coverage,
+        perfect_scores,
+        worst_scores,
+        gen_average: zip(gen_totals, gen_count).map(|x| x.0 / x.1).collect(),
+        typing_average: typing_output,
+        color_average: color_output,
+        category_average: category_output,
+        dual_type_average: dual_type_total / dual_type_count,
+        single_type_average: single_type_total / single_type_count,
+        manga_average: zip(manga_totals, manga_count).map(|x| x.0 / x.1).collect(),
+        anime_average: zip(anime_totals, anime_count).map(|x| x.0 / x.1).collect(),
+        evolutions_average: zip(evolutions_totals, evolutions_count).map(|x| x.0 / x.1).collect(),
+        stats_data: stats_data.get_result(),
+        matchup_data: matchup_data.get_result(),
+        matchup_summary: matchup_summary.into_iter().map(MatchupSummary::get_result).collect(),
+        stab_coverage_data: stab_coverage_data.get_result(),
+        overall_distribution,
+        gen_normalised: gen_distribution.iter().map(normalised).collect(),
+        typing_normalised: typing_distribution.iter().map(|x| (x.0, normalised(&x.1))).collect(),
+        color_normalised: color_distribution.iter().map(|x| (x.0, normalised(&x.1))).collect(),
+        category_normalised: category_distribution.iter().map(|x| (x.0, normalised(&x.1))).collect(),
+        single_type_normalised: normalised(&single_type_distribution),
+        dual_type_normalised: normalised(&dual_type_distribution),
+        stage_normalised: stage_distribution.iter().map(|x| (x.0, normalised(&x.1))).collect(),
+        gen_distribution,
+        typing_distribution,
+        color_distribution,
+        category_distribution,
+        single_type_distribution,
+        dual_type_distribution,
+        primary_type_distribution: primary_type_hist.into_iter().map(|x| (x.0, Distribution::from_histogram(x.1))).collect(),
+        secondary_type_distribution: secondary_type_hist.into_iter().map(|x| (x.0, Distribution::from_histogram(x.1))).collect(),
+        type_matrix: type_matrix.get_result(),
+        stage_distribution,
+        fully_evolved_distribution: Distribution::from_histogram(fully_evolved_hist),
+        evolving_distribution: Distribution::from_histogram(evolving_hist),
+        evolution_preference: evolution_preference(list, num_grades),
+        family_consistency: family_consistency(list, num_grades),
+        gen_typing,
+        gen_color,
+        best_combinations,
+        worst_combinations,
+        feature_importance: feature_importance(list, num_grades),
+        surprising_grades: surprising_grades(list, num_grades),
+    };
+}
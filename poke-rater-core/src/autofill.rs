@@ -0,0 +1,69 @@
+use crate::data::*;
+use crate::pokemon::*;
+use serde::Serialize;
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct AutofillChange {
+    pub id: PokemonId,
+    pub name: String,
+    pub old: Option<i32>,
+    pub new: i32,
+    // Index of the rule that set the grade
+    pub rule: usize,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct AutofillReport {
+    // Number of pokemon whose grade changed
+    pub changed: usize,
+    // Same, per rule in the order given
+    pub changed_per_rule: Vec<usize>,
+}
+
+impl AutofillReport {
+    pub fn new(changes: &[AutofillChange], rule_count: usize) -> AutofillReport {
+        let mut report = AutofillReport { changed: changes.len(), changed_per_rule: vec![0; rule_count] };
+        for change in changes {
+            report.changed_per_rule[change.rule] += 1;
+        }
+        return report;
+    }
+}
+
+/// Grades rules would give, leaving out pokemon whose grade stays the same
+pub fn autofill_changes(list: &[Pokemon], rules: &[AutofillRules]) -> Vec<AutofillChange> {
+    return list.iter()
+        .filter_map(|x| winning_rule(x, rules).map(|i| AutofillChange { id: x.id(), name: x.name.clone(), old: x.grade, new: rules[i].grade, rule: i }))
+        .collect();
+}
+
+/// Apply rules to list, see autofill_changes
+pub fn autofill(list: &mut [Pokemon], rules: &[AutofillRules]) -> AutofillReport {
+    let changes = autofill_changes(list, rules);
+    for pokemon in list.iter_mut() {
+        if let Some(i) = winning_rule(pokemon, rules) {
+            pokemon.grade = Some(rules[i].grade);
+            pokemon.grade_source = GradeSource::Autofill;
+        }
+    }
+    return AutofillReport::new(&changes, rules.len());
+}
+
+// Out of the rules that match and whose mode allows replacing the grade,
+// the highest priority wins, ties go to the later rule.
+// None if no rule applies or the winner gives the grade pokemon already has
+fn winning_rule(pokemon: &Pokemon, rules: &[AutofillRules]) -> Option<usize> {
+    let mut winner: Option<usize> = None;
+    for (i, rule) in rules.iter().enumerate() {
+        if is_rule_match(pokemon, rule) && rule.mode.allows(pokemon)
+            && winner.is_none_or(|w| rule.priority >= rules[w].priority) {
+            winner = Some(i);
+        }
+    }
+    return winner.filter(|i| pokemon.grade != Some(rules[*i].grade));
+}
+
+/// Check if pokemon fulfills rules
+pub fn is_rule_match(pokemon: &Pokemon, rule: &AutofillRules) -> bool {
+    return rule.rule.matches(pokemon);
+}
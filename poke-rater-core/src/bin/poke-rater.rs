@@ -0,0 +1,711 @@
+//! Headless command line interface for gradebooks
+//!
+//! poke-rater analyze <gradebook> [--grades N] [--json]
+//! poke-rater autofill <gradebook> --rules <rules.json> [--output <path>] [--dry-run]
+//! poke-rater validate <gradebook>
+//! poke-rater convert <gradebook.csv> [output]
+//! poke-rater tierlist <gradebook> [--svg <path>] [--png <path>] [--pics <dir>]
+//! poke-rater rescale <gradebook> --labels <a,b,c> [--output <path>]
+//! poke-rater compare <gradebook> <gradebook> [--json]
+//! poke-rater consensus <gradebook|dir>... [--rank] [--labels <a,b,c>] [--json]
+//! poke-rater matchups [--json]
+//!
+//! Every command takes [--dataset <slides.json>] (default: the dataset built into the binary)
+#![allow(clippy::needless_return)]
+use poke_rater_core::{data::*, pokemon::*, gradebook::*, dex::*, tierlist::*, compare::*, consensus::*, typechart::*};
+use std::{env, fs, process, iter::zip, path::{Path, PathBuf}, collections::HashMap, hash::Hash};
+use strum::IntoEnumIterator;
+
+const USAGE: &str = "Usage:
+    poke-rater analyze <gradebook> [--grades N] [--json]
+    poke-rater autofill <gradebook> --rules <rules.json> [--output <path>] [--dry-run]
+    poke-rater validate <gradebook>
+    poke-rater convert <gradebook.csv> [output]
+    poke-rater tierlist <gradebook> [--svg <path>] [--png <path>] [--pics <dir>]
+    poke-rater rescale <gradebook> --labels <a,b,c> [--output <path>]
+    poke-rater compare <gradebook> <gradebook> [--json]
+    poke-rater consensus <gradebook|dir>... [--rank] [--labels <a,b,c>] [--json]
+    poke-rater matchups [--json]
+
+Options:
+    --dataset <slides.json>   Pokemon dataset (default: built in)";
+
+/// Positional arguments and --flags
+struct Args {
+    positional: Vec<String>,
+    options: HashMap<String, Option<String>>,
+}
+
+impl Args {
+    fn parse(args: impl Iterator<Item = String>) -> Args {
+        // Flags that do not take a value
+        const SWITCHES: [&str; 3] = ["json", "dry-run", "rank"];
+        let mut positional = Vec::new();
+        let mut options = HashMap::new();
+        let mut args = args.peekable();
+
+        while let Some(arg) = args.next() {
+            match arg.strip_prefix("--") {
+                Some(name) if SWITCHES.contains(&name) => {
+                    options.insert(name.to_string(), None);
+                },
+                Some(name) => {
+                    options.insert(name.to_string(), args.next());
+                },
+                None => positional.push(arg)
+            };
+        }
+        return Args { positional, options };
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        return self.options.contains_key(name);
+    }
+
+    fn option(&self, name: &str) -> Result<Option<&str>, String> {
+        return match self.options.get(name) {
+            Some(Some(value)) => Ok(Some(value)),
+            Some(None) => Err(format!("--{} needs a value", name)),
+            None => Ok(None)
+        };
+    }
+
+    fn positional(&self, index: usize, name: &str) -> Result<&str, String> {
+        return self.positional.get(index).map(|x| x.as_str()).ok_or(format!("Missing <{}>", name));
+    }
+}
+
+fn main() {
+    let args = Args::parse(env::args().skip(1));
+    let result = match args.positional.first().map(|x| x.as_str()) {
+        Some("analyze") => analyze(&args),
+        Some("autofill") => autofill(&args),
+        Some("validate") => validate(&args),
+        Some("convert") => convert(&args),
+        Some("tierlist") => tierlist(&args),
+        Some("rescale") => rescale(&args),
+        Some("compare") => compare(&args),
+        Some("consensus") => group(&args),
+        Some("matchups") => matchups(&args),
+        Some(command) => Err(format!("Unknown command '{}'\n\n{}", command, USAGE)),
+        None => Err(USAGE.to_string())
+    };
+
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
+
+fn analyze(args: &Args) -> Result<(), String> {
+    let (dex, _) = load(args, args.positional(1, "gradebook")?)?;
+    let gradebook = dex.gradebook.as_ref().unwrap();
+    let num_grades = match args.option("grades")? {
+        Some(n) => n.parse::<i32>().ok().filter(|x| *x >= 1).ok_or(format!("Invalid --grades '{}', expected a number of at least 1", n))?,
+        None => gradebook.max_grade
+    };
+    let analysis = dex.analyze(num_grades)?;
+
+    if args.flag("json") {
+        println!("{}", serde_json::to_string_pretty(&analysis).map_err(|e| e.to_string())?);
+    } else {
+        print_analysis(&analysis, &gradebook.grade_labels);
+    }
+    return Ok(());
+}
+
+fn autofill(args: &Args) -> Result<(), String> {
+    let path = args.positional(1, "gradebook")?;
+    let rules_path = args.option("rules")?.ok_or("Missing --rules <rules.json>")?;
+    let rules: Vec<AutofillRules> = serde_json::from_str(&read(Path::new(rules_path))?)
+        .map_err(|e| format!("Could not parse {}: {}", rules_path, e))?;
+
+    let (mut dex, _) = load(args, path)?;
+    if args.flag("dry-run") {
+        let labels = &dex.gradebook.as_ref().unwrap().grade_labels;
+        let label = |grade: Option<i32>| grade.map_or(String::from("-"), |g| label(labels, (g - 1).max(0) as usize));
+        let changes = dex.preview_autofill(&rules);
+        for change in &changes {
+            println!("{:<24} {:>10} -> {:<10} ({})", change.name, label(change.old), label(Some(change.new)), rules[change.rule].rule);
+        }
+        println!("{} grades would change", changes.len());
+        return Ok(());
+    }
+    let report = dex.autofill(&rules);
+
+    let output = autofill_output(args, path)?;
+    let gradebook = dex.gradebook.clone().unwrap();
+    dex.save_gradebook(&output, gradebook.grade_labels, gradebook.cursor)?;
+    for (rule, changed) in zip(&rules, &report.changed_per_rule) {
+        println!("{:>5}  {} = {}", changed, rule.rule, rule.grade);
+    }
+    println!("Applied {} rules, {} grades changed. Saved {}", rules.len(), report.changed, output.display());
+    return Ok(());
+}
+
+fn validate(args: &Args) -> Result<(), String> {
+    let (dex, missing) = load(args, args.positional(1, "gradebook")?)?;
+    let gradebook = dex.gradebook.as_ref().unwrap();
+    let mut problems: Vec<String> = Vec::new();
+
+    for id in missing {
+        problems.push(format!("{} is not in the dataset", id_name(&id)));
+    }
+    if gradebook.max_grade as usize != gradebook.grade_labels.len() {
+        problems.push(format!("max_grade is {} but there are {} grade labels",
+            gradebook.max_grade, gradebook.grade_labels.len()));
+    }
+    for pokemon in &dex.list {
+        if let Some(g) = pokemon.grade.filter(|g| *g < 1 || *g > gradebook.max_grade) {
+            problems.push(format!("{} ({}) has grade {} outside 1..={}",
+                pokemon.name, id_name(&pokemon.id()), g, gradebook.max_grade));
+        }
+    }
+    if gradebook.dataset_version != DATASET_VERSION {
+        println!("Note: gradebook was written for dataset version {} (current: {})",
+            gradebook.dataset_version, DATASET_VERSION);
+    }
+
+    let unrated = dex.unrated().len();
+    println!("{} rated, {} unrated", dex.list.len() - unrated, unrated);
+    for problem in &problems {
+        println!("Error: {}", problem);
+    }
+    if !problems.is_empty() {
+        return Err(format!("{} problems found", problems.len()));
+    }
+    println!("OK");
+    return Ok(());
+}
+
+fn convert(args: &Args) -> Result<(), String> {
+    let path = args.positional(1, "gradebook.csv")?;
+    let (mut dex, _) = load(args, path)?;
+    let output = convert_output(args, path);
+    let gradebook = dex.gradebook.clone().unwrap();
+    dex.save_gradebook(&output, gradebook.grade_labels, gradebook.cursor)?;
+    println!("Saved {}", output.display());
+    return Ok(());
+}
+
+fn rescale(args: &Args) -> Result<(), String> {
+    let path = args.positional(1, "gradebook")?;
+    let labels: Vec<String> = args.option("labels")?.ok_or("Missing --labels <a,b,c>")?
+        .split(",").map(|x| x.trim().to_string()).collect();
+    let (mut dex, _) = load(args, path)?;
+    let output = rescale_output(args, path)?;
+    let old = dex.gradebook.as_ref().unwrap().max_grade;
+    let gradebook = dex.rescale_gradebook(labels)?.clone();
+    dex.save_gradebook(&output, gradebook.grade_labels.clone(), gradebook.cursor)?;
+    println!("Rescaled {} grades from {} to {} grades", gradebook.grades.len(), old, gradebook.max_grade);
+    println!("Saved {}", output.display());
+    return Ok(());
+}
+
+fn tierlist(args: &Args) -> Result<(), String> {
+    let path = args.positional(1, "gradebook")?;
+    let (dex, _) = load(args, path)?;
+    let gradebook = dex.gradebook.as_ref().unwrap();
+    let (svg, png, pics) = tierlist_outputs(args, path)?;
+
+    // Slide order keeps evolution lines together
+    let order: Vec<PokemonId> = dex.slides.iter().flatten().flatten().cloned().collect();
+    let tiers = build_tiers(&dex.list, &gradebook.grade_labels, Some(&order));
+    let style = TierListStyle::default();
+
+    fs::write(&svg, render_svg(&tiers, &pics, &style)).map_err(|e| format!("Could not write {}: {}", svg.display(), e))?;
+    fs::write(&png, render_png(&tiers, &pics, &style)?).map_err(|e| format!("Could not write {}: {}", png.display(), e))?;
+
+    println!("Saved {} and {}", svg.display(), png.display());
+    return Ok(());
+}
+
+fn compare(args: &Args) -> Result<(), String> {
+    let (dex, _) = load(args, args.positional(1, "gradebook")?)?;
+    let a = dex.gradebook.as_ref().unwrap();
+    let b = Gradebook::load(Path::new(args.positional(2, "gradebook")?), &dex.list)?;
+    let comparison = compare_gradebooks(&dex.list, a, &b);
+
+    if args.flag("json") {
+        println!("{}", serde_json::to_string_pretty(&comparison).map_err(|e| e.to_string())?);
+    } else {
+        print_comparison(&comparison, &a.grade_labels, &b.grade_labels);
+    }
+    return Ok(());
+}
+
+// Named group() as consensus() is the library function
+fn group(args: &Args) -> Result<(), String> {
+    // Directories contribute every gradebook in them
+    let mut paths: Vec<(String, PathBuf)> = Vec::new();
+    for arg in args.positional.iter().skip(1).map(Path::new) {
+        if arg.is_dir() {
+            paths.extend(find_gradebooks(arg)?);
+        } else {
+            paths.push((gradebook_name(arg), arg.to_path_buf()));
+        }
+    }
+    if paths.len() < 2 {
+        return Err(String::from("Consensus needs at least 2 gradebooks"));
+    }
+
+    let (dex, _) = load(args, &paths[0].1.to_string_lossy())?;
+    let mut gradebooks: Vec<(String, Gradebook)> = Vec::new();
+    for (name, path) in paths {
+        gradebooks.push((name, Gradebook::load(&path, &dex.list)?));
+    }
+    let labels: Vec<String> = match args.option("labels")? {
+        Some(l) => l.split(",").map(|x| x.trim().to_string()).collect(),
+        None => gradebooks[0].1.grade_labels.clone()
+    };
+    let normalisation = if args.flag("rank") { Normalisation::Rank } else { Normalisation::ZScore };
+    let output = consensus(&dex.list, &gradebooks, normalisation, &labels);
+
+    if args.flag("json") {
+        println!("{}", serde_json::to_string_pretty(&output).map_err(|e| e.to_string())?);
+    } else {
+        print_consensus(&output, &dex.list);
+    }
+    return Ok(());
+}
+
+fn matchups(args: &Args) -> Result<(), String> {
+    let dex = load_dataset(args)?;
+    let mismatches = check_matchups(&dex.list);
+
+    if args.flag("json") {
+        println!("{}", serde_json::to_string_pretty(&mismatches).map_err(|e| e.to_string())?);
+    } else {
+        let percent = |x: i32| format!("{}%", x);
+        let rows: Vec<Vec<String>> = mismatches.iter()
+            .map(|x| {
+                let ability = ability(&dex.list[find_pokemon(&dex.list, &x.id).unwrap()]);
+                vec![
+                    x.name.clone(),
+                    id_name(&x.id),
+                    format!("{:?}", x.attack),
+                    percent(x.scraped),
+                    percent(x.computed),
+                    ability.map_or(String::from("-"), |a| format!("{:?}", a)),
+                ]
+            })
+            .collect();
+        let headers = ["Pokemon", "Id", "Attack", "Scraped", "Computed", "Ability"].map(String::from);
+        print_table("Matchup mismatches", &headers, &rows);
+    }
+    if !mismatches.is_empty() {
+        return Err(format!("{} matchups disagree with the type chart", mismatches.len()));
+    }
+    println!("OK");
+    return Ok(());
+}
+
+/* Helpers */
+
+fn read(path: &Path) -> Result<String, String> {
+    return fs::read_to_string(path).map_err(|e| format!("Could not read {}: {}", path.display(), e));
+}
+
+/// Load and validate dataset, then load gradebook. Returns gradebook ids that are not in the dataset
+fn load(args: &Args, gradebook: &str) -> Result<(Dex, Vec<PokemonId>), String> {
+    let mut dex = load_dataset(args)?;
+    let missing = dex.load_gradebook(Path::new(gradebook))?;
+    return Ok((dex, missing));
+}
+
+/// Load and validate --dataset, or the built in dataset
+fn load_dataset(args: &Args) -> Result<Dex, String> {
+    return match args.option("dataset")? {
+        Some(path) => Dex::load(&read(Path::new(path))?),
+        None => Dex::embedded()
+    }.map_err(|e| e.to_string());
+}
+
+/// <name>.csv -> <name>.gradebook.json, .gradebook.json is overwritten
+fn gradebook_output(path: &Path) -> PathBuf {
+    return sibling(path, GRADEBOOK_EXTENSION);
+}
+
+/// <name>.<extension> next to the gradebook at path
+fn sibling(path: &Path, extension: &str) -> PathBuf {
+    return path.with_file_name(format!("{}.{}", gradebook_name(path), extension));
+}
+
+/// --output, or the gradebook itself
+fn autofill_output(args: &Args, path: &str) -> Result<PathBuf, String> {
+    return Ok(args.option("output")?.map_or(gradebook_output(Path::new(path)), PathBuf::from));
+}
+
+/// [output], or <name>.gradebook.json
+fn convert_output(args: &Args, path: &str) -> PathBuf {
+    return args.positional.get(2).map_or(gradebook_output(Path::new(path)), PathBuf::from);
+}
+
+/// --output, or <name>.rescaled.gradebook.json so the original is never replaced
+fn rescale_output(args: &Args, path: &str) -> Result<PathBuf, String> {
+    let default = sibling(Path::new(path), &format!("rescaled.{}", GRADEBOOK_EXTENSION));
+    return Ok(args.option("output")?.map_or(default, PathBuf::from));
+}
+
+/// --svg, --png and --pics, defaulting to <name>.tierlist.svg and .png and the app's sprites
+fn tierlist_outputs(args: &Args, path: &str) -> Result<(PathBuf, PathBuf, PathBuf), String> {
+    // Sprites used by the app, found from the source tree so the working directory does not matter
+    let default_pics = Path::new(env!("CARGO_MANIFEST_DIR")).join("../src/assets/pics");
+    let svg = args.option("svg")?.map_or(sibling(Path::new(path), "tierlist.svg"), PathBuf::from);
+    let png = args.option("png")?.map_or(sibling(Path::new(path), "tierlist.png"), PathBuf::from);
+    let pics = args.option("pics")?.map_or(default_pics, PathBuf::from);
+    return Ok((svg, png, pics));
+}
+
+/// File name without .gradebook.json or .csv
+fn gradebook_name(path: &Path) -> String {
+    let name = path.file_name().map_or(String::new(), |x| x.to_string_lossy().to_string());
+    let stem = name.strip_suffix(&format!(".{}", GRADEBOOK_EXTENSION))
+        .or(name.strip_suffix(&format!(".{}", LEGACY_EXTENSION)))
+        .unwrap_or(&name);
+    return stem.to_string();
+}
+
+fn id_name(id: &PokemonId) -> String {
+    return match &id.form {
+        Some(form) => format!("#{}-{}", id.dex_no, form),
+        None => format!("#{}", id.dex_no)
+    };
+}
+
+/* Human readable output */
+
+fn print_table(title: &str, headers: &[String], rows: &[Vec<String>]) {
+    let mut widths: Vec<usize> = headers.iter().map(|x| x.len()).collect();
+    for row in rows {
+        for (i, cell) in row.iter().enumerate() {
+            widths[i] = widths[i].max(cell.len());
+        }
+    }
+    let line = |cells: &[String]| cells.iter().enumerate()
+        .map(|(i, x)| format!("{:<width$}", x, width = widths[i]))
+        .collect::<Vec<String>>()
+        .join("  ");
+
+    println!("\n{}", title);
+    println!("{}", line(headers));
+    println!("{}", widths.iter().map(|x| "-".repeat(*x)).collect::<Vec<String>>().join("  "));
+    for row in rows {
+        println!("{}", line(row));
+    }
+}
+
+fn label(labels: &[String], grade: usize) -> String {
+    return labels.get(grade).cloned().unwrap_or((grade + 1).to_string());
+}
+
+// Averages are 0-indexed grades, shown as 1-indexed
+fn avg(value: f64) -> String {
+    return if value.is_nan() { String::from("-") } else { format!("{:.2}", value + 1.0) };
+}
+
+// Spread of grades, not shifted like averages
+fn spread(value: f64) -> String {
+    return if value.is_nan() { String::from("-") } else { format!("{:.2}", value) };
+}
+
+// Median, SD and CI columns
+fn dispersion(distribution: &Distribution) -> Vec<String> {
+    let ci = if distribution.ci_low.is_nan() {
+        String::from("-")
+    } else {
+        format!("{}-{}", avg(distribution.ci_low), avg(distribution.ci_high))
+    };
+    return vec![avg(distribution.median), spread(distribution.std_dev), ci];
+}
+
+// 0-1 score and percentile columns
+fn normalised(score: &NormalisedScore) -> Vec<String> {
+    if score.score.is_nan() {
+        return vec![String::from("-"), String::from("-")];
+    }
+    return vec![format!("{:.2}", score.score), format!("{:.0}", score.percentile)];
+}
+
+// Averages with row and column names. Column names are abbreviated so up to 18 columns fit
+fn matrix_table(title: &str, rows: &[String], columns: &[String], averages: &[Vec<f64>]) {
+    let mut headers = vec![String::new()];
+    headers.extend(columns.iter().map(|x| x.chars().take(3).collect::<String>()));
+    let rows: Vec<Vec<String>> = rows.iter().zip(averages)
+        .map(|(name, row)| [vec![name.clone()], row.iter().map(|x| if x.is_nan() { String::from("-") } else { format!("{:.1}", x + 1.0) }).collect()].concat())
+        .collect();
+    print_table(title, &headers, &rows);
+}
+
+fn per_grade_table<T>(title: &str, data: &[HashMap<T, f64>], columns: &[T], labels: &[String])
+    where T: Hash + Eq + std::fmt::Debug {
+    let mut headers = vec![String::from("Grade")];
+    headers.extend(columns.iter().map(|x| format!("{:?}", x)));
+
+    let rows: Vec<Vec<String>> = data.iter().enumerate().map(|(grade, values)| {
+        let mut row = vec![label(labels, grade)];
+        row.extend(columns.iter().map(|x| values.get(x).map_or(String::from("-"), |v| format!("{:.1}", v))));
+        row
+    }).collect();
+    print_table(title, &headers, &rows);
+}
+
+// Correlation or normalised difference
+fn ratio(value: f64) -> String {
+    return if value.is_nan() { String::from("-") } else { format!("{:+.2}", value) };
+}
+
+fn print_comparison(comparison: &ComparisonOutput, labels_a: &[String], labels_b: &[String]) {
+    println!("Rated in both: {}  Only first: {}  Only second: {}", comparison.count, comparison.only_a, comparison.only_b);
+    println!("Spearman: {}  Kendall: {}  Mean difference: {}  Bias: {}", ratio(comparison.overall.spearman),
+        ratio(comparison.kendall), spread(comparison.overall.mean_difference), ratio(comparison.overall.mean_bias));
+
+    let headers = ["Spearman", "Mean difference", "Bias", "Rated"].map(String::from);
+    let row = |name: String, x: &Agreement| vec![name, ratio(x.spearman), spread(x.mean_difference), ratio(x.mean_bias), x.count.to_string()];
+    let rows: Vec<Vec<String>> = comparison.gen_agreement.iter().enumerate()
+        .map(|(i, x)| row((i + 1).to_string(), x))
+        .collect();
+    print_table("Generations", &[vec![String::from("Generation")], headers.to_vec()].concat(), &rows);
+    let rows: Vec<Vec<String>> = comparison.typing_agreement.iter()
+        .map(|x| row(format!("{:?}", x.0), &x.1))
+        .collect();
+    print_table("Types", &[vec![String::from("Type")], headers.to_vec()].concat(), &rows);
+
+    let headers = ["Pokemon", "First", "Second"].map(String::from);
+    let rows: Vec<Vec<String>> = comparison.disagreements.iter()
+        .map(|x| vec![x.name.clone(), label(labels_a, (x.grade_a - 1) as usize), label(labels_b, (x.grade_b - 1) as usize)])
+        .collect();
+    print_table("Biggest disagreements", &headers, &rows);
+}
+
+fn print_consensus(output: &ConsensusOutput, list: &[Pokemon]) {
+    let name = |id: &PokemonId| find_pokemon(list, id).map_or(id_name(id), |i| list[i].name.clone());
+    println!("Consensus tier list ({:?})", output.normalisation);
+    for tier in &output.tiers {
+        println!("{}: {}", tier.label, tier.pokemon.iter().map(name).collect::<Vec<String>>().join(", "));
+    }
+
+    let headers = ["Pokemon", "Score", "Variance", "Raters"].map(String::from);
+    let rows: Vec<Vec<String>> = output.controversial.iter()
+        .map(|x| vec![x.name.clone(), ratio(x.score), spread(x.controversy), x.raters.to_string()])
+        .collect();
+    print_table("Most controversial", &headers, &rows);
+
+    let headers = ["Rater", "Rated", "Agreement", "Most contrarian picks"].map(String::from);
+    let rows: Vec<Vec<String>> = output.raters.iter()
+        .map(|x| {
+            let picks = x.contrarian.iter()
+                .map(|c| format!("{} ({} {})", c.name, c.grade, if c.difference > 0.0 { "higher" } else { "lower" }))
+                .collect::<Vec<String>>();
+            vec![x.name.clone(), x.count.to_string(), ratio(x.agreement), picks.join(", ")]
+        })
+        .collect();
+    print_table("Raters", &headers, &rows);
+}
+
+fn print_analysis(analysis: &AnalysisOutput, labels: &[String]) {
+    let coverage = &analysis.coverage;
+    println!("Rated: {}  Unrated: {}", coverage.rated, coverage.unrated);
+
+    println!("Median: {}  SD: {}  Histogram: {:?}", avg(analysis.overall_distribution.median),
+        spread(analysis.overall_distribution.std_dev), analysis.overall_distribution.histogram);
+
+    let headers = ["Generation", "Avg grade", "Median", "SD", "95% CI", "Score", "Percentile", "Rated", "Unrated"].map(String::from);
+    let rows: Vec<Vec<String>> = analysis.gen_average.iter().enumerate()
+        .map(|(i, x)| [vec![(i + 1).to_string(), avg(*x)], dispersion(&analysis.gen_distribution[i]),
+            normalised(&analysis.gen_normalised[i]), vec![coverage.gen[i].0.to_string(), coverage.gen[i].1.to_string()]].concat())
+        .collect();
+    print_table("Generations", &headers, &rows);
+
+    let headers = ["Type", "Avg grade", "Median", "SD", "95% CI", "Score", "Percentile", "Rated"].map(String::from);
+    let rows: Vec<Vec<String>> = analysis.typing_average.iter()
+        .map(|x| {
+            let distribution = &analysis.typing_distribution.iter().find(|d| d.0 == x.0).unwrap().1;
+            let score = &analysis.typing_normalised.iter().find(|d| d.0 == x.0).unwrap().1;
+            [vec![format!("{:?}", x.0), avg(x.1)], dispersion(distribution), normalised(score), vec![distribution.count.to_string()]].concat()
+        })
+        .collect();
+    print_table("Types", &headers, &rows);
+    println!("Single type: {} (n={})  Dual type: {} (n={})",
+        avg(analysis.single_type_average), analysis.single_type_distribution.count,
+        avg(analysis.dual_type_average), analysis.dual_type_distribution.count);
+
+    let headers = ["Type", "As first type", "Rated", "As second type", "Rated"].map(String::from);
+    let rows: Vec<Vec<String>> = zip(&analysis.primary_type_distribution, &analysis.secondary_type_distribution)
+        .map(|(p, s)| vec![format!("{:?}", p.0), avg(p.1.mean), p.1.count.to_string(), avg(s.1.mean), s.1.count.to_string()])
+        .collect();
+    print_table("First and second types", &headers, &rows);
+
+    let types: Vec<String> = analysis.type_matrix.types.iter().map(|x| format!("{:?}", x)).collect();
+    matrix_table("Type combinations (row: first type, column: second type, diagonal: single type)",
+        &types, &types, &analysis.type_matrix.averages);
+
+    let headers = ["Color", "Avg grade", "Median", "SD", "95% CI", "Score", "Percentile", "Rated"].map(String::from);
+    let rows: Vec<Vec<String>> = analysis.color_average.iter()
+        .map(|x| {
+            let distribution = &analysis.color_distribution.iter().find(|d| d.0 == x.0).unwrap().1;
+            let score = &analysis.color_normalised.iter().find(|d| d.0 == x.0).unwrap().1;
+            [vec![format!("{:?}", x.0), avg(x.1)], dispersion(distribution), normalised(score), vec![distribution.count.to_string()]].concat()
+        })
+        .collect();
+    print_table("Colors", &headers, &rows);
+
+    let headers = ["Category", "Avg grade", "Median", "SD", "95% CI", "Score", "Percentile", "Rated"].map(String::from);
+    let rows: Vec<Vec<String>> = analysis.category_average.iter()
+        .map(|x| {
+            let distribution = &analysis.category_distribution.iter().find(|d| d.0 == x.0).unwrap().1;
+            let score = &analysis.category_normalised.iter().find(|d| d.0 == x.0).unwrap().1;
+            [vec![format!("{:?}", x.0), avg(x.1)], dispersion(distribution), normalised(score), vec![distribution.count.to_string()]].concat()
+        })
+        .collect();
+    print_table("Categories", &headers, &rows);
+
+    let gens: Vec<String> = (1..=GEN_COUNT).map(|x| format!("Gen {}", x)).collect();
+    let columns: Vec<String> = analysis.gen_typing.columns.iter().map(|x| format!("{:?}", x)).collect();
+    matrix_table("Generation and type", &gens, &columns, &analysis.gen_typing.averages);
+    let columns: Vec<String> = analysis.gen_color.columns.iter().map(|x| format!("{:?}", x)).collect();
+    matrix_table("Generation and color", &gens, &columns, &analysis.gen_color.averages);
+
+    let headers = ["Combination", "Avg grade", "Rated"].map(String::from);
+    for (title, list) in [("Favorite combinations", &analysis.best_combinations), ("Least favorite combinations", &analysis.worst_combinations)] {
+        let rows: Vec<Vec<String>> = list.iter()
+            .map(|x| vec![format!("Gen {} {}", x.gen_no, x.feature), avg(x.average), x.count.to_string()])
+            .collect();
+        print_table(&format!("{} (at least {} rated)", title, MIN_COMBINATION_COUNT), &headers, &rows);
+    }
+
+    let headers = ["Stage", "Avg grade", "Median", "SD", "95% CI", "Score", "Percentile", "Rated"].map(String::from);
+    let rows: Vec<Vec<String>> = zip(&analysis.stage_distribution, &analysis.stage_normalised)
+        .map(|(x, score)| [vec![format!("{:?}", x.0), avg(x.1.mean)], dispersion(&x.1), normalised(&score.1), vec![x.1.count.to_string()]].concat())
+        .collect();
+    print_table("Evolution stages", &headers, &rows);
+    println!("Fully evolved: {} (n={})  Can evolve: {} (n={})",
+        avg(analysis.fully_evolved_distribution.mean), analysis.fully_evolved_distribution.count,
+        avg(analysis.evolving_distribution.mean), analysis.evolving_distribution.count);
+    let preference = &analysis.evolution_preference;
+    println!("Within {} families: final evolution preferred in {}, first stage in {}, tied in {} (mean difference {})",
+        preference.families, preference.prefer_final, preference.prefer_basic, preference.ties, spread(preference.mean_difference));
+    let deviations: Vec<String> = preference.stage_deviation.iter()
+        .map(|x| if x.1.is_nan() { format!("{:?} -", x.0) } else { format!("{:?} {:+.2}", x.0, x.1) })
+        .collect();
+    println!("Grade compared to the rest of the family: {}", deviations.join("  "));
+
+    let consistency = &analysis.family_consistency;
+    let headers = ["Family", "Grades", "Spread", "First to last stage"].map(String::from);
+    let rows: Vec<Vec<String>> = consistency.widest.iter()
+        .map(|x| {
+            let members: Vec<String> = x.members.iter().map(|m| format!("{} {}", m.0, label(labels, (m.1 - 1) as usize))).collect();
+            let trend = if x.trend.is_nan() { String::from("-") } else { format!("{:+.2}", x.trend) };
+            vec![x.name.clone(), members.join(", "), spread(x.spread), trend]
+        })
+        .collect();
+    print_table("Most split families", &headers, &rows);
+    println!("{} of {} families with 2+ rated pokemon rated uniformly, mean spread {}",
+        consistency.uniform.len(), consistency.families, spread(consistency.mean_spread));
+    let stages: Vec<String> = consistency.stage_average.iter().map(|x| format!("{:?} {}", x.0, avg(x.1))).collect();
+    println!("Average grade from first to last stage: {}  (mean change {})", stages.join("  "), spread(consistency.mean_trend));
+
+    let headers = ["Grade", "Avg anime", "Avg manga", "Avg evolutions"].map(String::from);
+    let rows: Vec<Vec<String>> = analysis.anime_average.iter().zip(&analysis.manga_average).zip(&analysis.evolutions_average).enumerate()
+        .map(|(i, ((anime, manga), evolutions))| vec![label(labels, i), format!("{:.2}", anime), format!("{:.2}", manga), format!("{:.2}", evolutions)])
+        .collect();
+    print_table("Appearances and evolutions", &headers, &rows);
+
+    per_grade_table("Stats", &analysis.stats_data, &StatNames::iter().collect::<Vec<StatNames>>(), labels);
+    per_grade_table("Matchups (damage taken, %)", &analysis.matchup_data, &PTypes::iter().collect::<Vec<PTypes>>(), labels);
+    per_grade_table("Super effective coverage of own types (% of pokemon)", &analysis.stab_coverage_data, &PTypes::iter().collect::<Vec<PTypes>>(), labels);
+
+    let headers = ["Grade", "Weaknesses", "Resistances", "Immunities", "Hits super effectively", "Walled by", "Rated"].map(String::from);
+    let rows: Vec<Vec<String>> = analysis.matchup_summary.iter().enumerate()
+        .map(|(i, x)| [vec![label(labels, i)], [x.weaknesses, x.resistances, x.immunities, x.super_effective, x.walled_by].iter().map(|v| spread(*v)).collect(), vec![x.count.to_string()]].concat())
+        .collect();
+    print_table("Average matchups (number of types)", &headers, &rows);
+
+    if let Some(importance) = &analysis.feature_importance {
+        let headers = ["Factor", "Effect"].map(String::from);
+        let count = importance.factors.len();
+        let rows: Vec<Vec<String>> = importance.factors.iter().enumerate()
+            .filter(|(i, _)| *i < 5 || *i >= count.saturating_sub(5))
+            .map(|(_, x)| vec![x.feature.to_string(), format!("{:+.2}", x.effect)])
+            .collect();
+        print_table("What drives your grades", &headers, &rows);
+        println!("Explains {:.0}% of grade variance ({} pokemon)", importance.r_squared * 100.0, importance.count);
+    }
+
+    if let Some(surprises) = &analysis.surprising_grades {
+        let headers = ["Pokemon", "Grade", "Expected"].map(String::from);
+        for (title, list) in [("Rated higher than expected", &surprises.higher), ("Rated lower than expected", &surprises.lower)] {
+            let rows: Vec<Vec<String>> = list.iter()
+                .map(|x| vec![x.name.clone(), label(labels, (x.grade - 1) as usize), format!("{:.1}", x.predicted)])
+                .collect();
+            print_table(title, &headers, &rows);
+        }
+    }
+
+    println!("\nPerfect scores: {}", analysis.perfect_scores.join(", "));
+    println!("Worst scores: {}", analysis.worst_scores.join(", "));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(text: &str) -> Args {
+        return Args::parse(text.split_whitespace().map(String::from));
+    }
+
+    #[test]
+    fn test_args() {
+        let parsed = args("autofill a.gradebook.json --rules r.json --dry-run --json");
+        assert_eq!(parsed.positional, vec!["autofill", "a.gradebook.json"]);
+        assert_eq!(parsed.option("rules"), Ok(Some("r.json")));
+        assert!(parsed.flag("dry-run") && parsed.flag("json") && !parsed.flag("rank"));
+        assert!(parsed.option("json").is_err());
+        assert_eq!(parsed.option("output"), Ok(None));
+        assert!(parsed.positional(2, "gradebook").is_err());
+
+        // Option missing its value at the end
+        assert!(args("rescale a.gradebook.json --labels").option("labels").is_err());
+    }
+
+    #[test]
+    fn test_gradebook_name() {
+        assert_eq!(gradebook_name(Path::new("dir/foo.gradebook.json")), "foo");
+        assert_eq!(gradebook_name(Path::new("foo.csv")), "foo");
+        assert_eq!(gradebook_name(Path::new("foo.txt")), "foo.txt");
+    }
+
+    #[test]
+    fn test_autofill_output() {
+        assert_eq!(autofill_output(&args("autofill dir/foo.gradebook.json"), "dir/foo.gradebook.json"),
+            Ok(PathBuf::from("dir/foo.gradebook.json")));
+        assert_eq!(autofill_output(&args("autofill foo.csv"), "foo.csv"), Ok(PathBuf::from("foo.gradebook.json")));
+        assert_eq!(autofill_output(&args("autofill foo.csv --output bar.gradebook.json"), "foo.csv"),
+            Ok(PathBuf::from("bar.gradebook.json")));
+    }
+
+    #[test]
+    fn test_convert_output() {
+        assert_eq!(convert_output(&args("convert dir/foo.csv"), "dir/foo.csv"), PathBuf::from("dir/foo.gradebook.json"));
+        assert_eq!(convert_output(&args("convert foo.csv bar.json"), "foo.csv"), PathBuf::from("bar.json"));
+    }
+
+    #[test]
+    fn test_rescale_output() {
+        // Never the input gradebook
+        assert_eq!(rescale_output(&args("rescale dir/foo.gradebook.json"), "dir/foo.gradebook.json"),
+            Ok(PathBuf::from("dir/foo.rescaled.gradebook.json")));
+        assert_eq!(rescale_output(&args("rescale foo.csv --output bar.gradebook.json"), "foo.csv"),
+            Ok(PathBuf::from("bar.gradebook.json")));
+    }
+
+    #[test]
+    fn test_tierlist_outputs() {
+        let (svg, png, pics) = tierlist_outputs(&args("tierlist dir/foo.gradebook.json"), "dir/foo.gradebook.json").unwrap();
+        assert_eq!((svg, png), (PathBuf::from("dir/foo.tierlist.svg"), PathBuf::from("dir/foo.tierlist.png")));
+        assert!(pics.is_absolute() && pics.is_dir(), "{}", pics.display());
+
+        let (svg, png, pics) = tierlist_outputs(&args("tierlist foo.csv --svg a.svg --png b.png --pics p"), "foo.csv").unwrap();
+        assert_eq!((svg, png, pics), (PathBuf::from("a.svg"), PathBuf::from("b.png"), PathBuf::from("p")));
+    }
+}
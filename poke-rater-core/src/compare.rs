@@ -0,0 +1,100 @@
+use crate::data::*;
+use crate::pokemon::*;
+use crate::gradebook::*;
+use crate::stats::*;
+use serde::Serialize;
+use strum::IntoEnumIterator;
+
+// Pokemon listed in ComparisonOutput.disagreements
+pub const DISAGREEMENT_COUNT: usize = 10;
+
+/// How closely two raters agree on a group of pokemon.
+/// Uses normalised grades, so gradebooks with different scales can be compared
+#[derive(Serialize, Debug, Clone)]
+pub struct Agreement {
+    pub count: usize,
+    pub spearman: f64,
+    // Mean |a - b|: 0 is identical grades, 1 is opposite ends of the scale
+    pub mean_difference: f64,
+    // Mean a - b: positive if the first gradebook rates the group higher
+    pub mean_bias: f64,
+}
+
+impl Agreement {
+    fn new(a: &[f64], b: &[f64]) -> Agreement {
+        let differences: Vec<f64> = a.iter().zip(b).map(|(x, y)| x - y).collect();
+        return Agreement {
+            count: a.len(),
+            spearman: spearman(a, b),
+            mean_difference: mean(&differences.iter().map(|x| x.abs()).collect::<Vec<f64>>()),
+            mean_bias: mean(&differences),
+        };
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct Disagreement {
+    pub id: PokemonId,
+    pub name: String,
+    pub grade_a: i32,
+    pub grade_b: i32,
+    // Grades in each gradebook's own labels, since the scales may differ
+    pub label_a: String,
+    pub label_b: String,
+    // Normalised a - b
+    pub difference: f64,
+}
+
+// Grade's label in gradebook, the grade itself if the gradebook has no label for it
+fn label(gradebook: &Gradebook, grade: i32) -> String {
+    return gradebook.grade_labels.get((grade - 1) as usize).cloned().unwrap_or(grade.to_string());
+}
+
+/// Agreement between two gradebooks on the pokemon both have rated
+pub fn compare_gradebooks(list: &[Pokemon], a: &Gradebook, b: &Gradebook) -> ComparisonOutput {
+    let (grades_a, grades_b) = (a.grades_for(list), b.grades_for(list));
+
+    // Pokemon rated in both: (pokemon, grade a, grade b)
+    let mut both: Vec<(&Pokemon, i32, i32)> = Vec::new();
+    let (mut only_a, mut only_b) = (0, 0);
+    for (pokemon, (x, y)) in list.iter().zip(grades_a.iter().zip(&grades_b)) {
+        match (x, y) {
+            (Some(x), Some(y)) => both.push((pokemon, *x, *y)),
+            (Some(_), None) => only_a += 1,
+            (None, Some(_)) => only_b += 1,
+            (None, None) => ()
+        };
+    }
+    let normalised = |p: &(&Pokemon, i32, i32)| (a.normalise(p.1), b.normalise(p.2));
+
+    let agreement = |filter: &dyn Fn(&Pokemon) -> bool| {
+        let (x, y): (Vec<f64>, Vec<f64>) = both.iter().filter(|p| filter(p.0)).map(normalised).unzip();
+        return Agreement::new(&x, &y);
+    };
+    let (x, y): (Vec<f64>, Vec<f64>) = both.iter().map(normalised).unzip();
+
+    let mut disagreements: Vec<Disagreement> = both.iter()
+        .map(|(pokemon, x, y)| Disagreement {
+            id: pokemon.id(),
+            name: pokemon.name.clone(),
+            grade_a: *x,
+            grade_b: *y,
+            label_a: label(a, *x),
+            label_b: label(b, *y),
+            difference: a.normalise(*x) - b.normalise(*y),
+        })
+        .collect();
+    disagreements.sort_by(|x, y| y.difference.abs().total_cmp(&x.difference.abs()));
+    disagreements.truncate(DISAGREEMENT_COUNT);
+
+    return ComparisonOutput {
+        count: both.len(),
+        only_a,
+        only_b,
+        kendall: kendall(&x, &y),
+        overall: Agreement::new(&x, &y),
+        gen_agreement: (1..=GEN_COUNT).map(|gen| agreement(&|p| p.is_gen(&gen))).collect(),
+        typing_agreement: PTypes::iter().map(|t| (t, agreement(&|p| p.is_typing(&t)))).collect(),
+        disagreements,
+    };
+}
@@ -0,0 +1,179 @@
+use crate::data::*;
+use crate::pokemon::*;
+use crate::gradebook::*;
+use crate::stats::*;
+use crate::tierlist::build_tiers;
+use serde::{Deserialize, Serialize};
+
+// Pokemon listed in ConsensusOutput.controversial
+pub const CONTROVERSY_COUNT: usize = 10;
+// Pokemon listed in Rater.contrarian
+pub const CONTRARIAN_COUNT: usize = 5;
+
+/// How each rater's grades are put on a shared scale
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Normalisation {
+    // Standard deviations from the rater's mean grade
+    #[default]
+    ZScore,
+    // Percentile among the rater's grades, 0 (lowest) to 1 (highest). Ignores how far apart grades are
+    Rank,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ConsensusPokemon {
+    pub id: PokemonId,
+    pub name: String,
+    // Mean normalised score across raters
+    pub score: f64,
+    // Sample variance of normalised scores, NaN if rated by one person
+    pub controversy: f64,
+    pub raters: usize,
+    // Tier in the consensus tier list
+    pub grade: i32,
+}
+
+/// Pick where a rater differs most from everyone else
+#[derive(Serialize, Debug, Clone)]
+pub struct Contrarian {
+    pub id: PokemonId,
+    pub name: String,
+    pub grade: i32,
+    // Rater's normalised score minus the mean of the other raters
+    pub difference: f64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct Rater {
+    pub name: String,
+    pub count: usize,
+    // Spearman correlation with the other raters' mean score
+    pub agreement: f64,
+    // Largest difference first
+    pub contrarian: Vec<Contrarian>,
+}
+
+/// Combine gradebooks (possibly with different grade scales) into one ranking.
+/// Tiers use grade_labels, sized to match how often raters gave each part of their scale
+pub fn consensus(list: &[Pokemon], gradebooks: &[(String, Gradebook)], normalisation: Normalisation, grade_labels: &[String]) -> ConsensusOutput {
+    // scores[rater][pokemon]
+    let grades: Vec<Vec<Option<i32>>> = gradebooks.iter().map(|x| x.1.grades_for(list)).collect();
+    let scores: Vec<Vec<Option<f64>>> = grades.iter().map(|x| normalise(x, normalisation)).collect();
+
+    let mut ranking: Vec<ConsensusPokemon> = list.iter().enumerate()
+        .filter_map(|(i, pokemon)| {
+            let values: Vec<f64> = scores.iter().filter_map(|x| x[i]).collect();
+            if values.is_empty() {
+                return None;
+            }
+            let score = mean(&values);
+            let controversy = values.iter().map(|x| (x - score).powi(2)).sum::<f64>() / (values.len() - 1) as f64;
+            Some(ConsensusPokemon { id: pokemon.id(), name: pokemon.name.clone(), score, controversy, raters: values.len(), grade: 0 })
+        })
+        .collect();
+    ranking.sort_by(|x, y| y.score.total_cmp(&x.score));
+    assign_grades(&mut ranking, gradebooks, &grades, grade_labels.len());
+
+    let mut controversial: Vec<ConsensusPokemon> = ranking.iter().filter(|x| x.raters > 1).cloned().collect();
+    controversial.sort_by(|x, y| y.controversy.total_cmp(&x.controversy));
+    controversial.truncate(CONTROVERSY_COUNT);
+
+    let raters = gradebooks.iter().enumerate()
+        .map(|(r, (name, _))| rater(list, name, r, &grades, &scores))
+        .collect();
+
+    let mut graded: Vec<Pokemon> = list.to_vec();
+    for pokemon in graded.iter_mut() {
+        pokemon.grade = None;
+    }
+    for x in &ranking {
+        if let Some(i) = find_pokemon(&graded, &x.id) {
+            graded[i].grade = Some(x.grade);
+        }
+    }
+    let order: Vec<PokemonId> = ranking.iter().map(|x| x.id.clone()).collect();
+
+    return ConsensusOutput {
+        normalisation,
+        raters,
+        tiers: build_tiers(&graded, grade_labels, Some(&order)),
+        ranking,
+        controversial,
+    };
+}
+
+// Normalised score of each rated pokemon
+fn normalise(grades: &[Option<i32>], normalisation: Normalisation) -> Vec<Option<f64>> {
+    let rated: Vec<f64> = grades.iter().flatten().map(|x| *x as f64).collect();
+    let n = rated.len() as f64;
+    let scores: Vec<f64> = match normalisation {
+        Normalisation::ZScore => {
+            let m = mean(&rated);
+            let sd = (rated.iter().map(|x| (x - m).powi(2)).sum::<f64>() / n).sqrt();
+            rated.iter().map(|x| if sd > 0.0 { (x - m) / sd } else { 0.0 }).collect()
+        },
+        Normalisation::Rank => ranks(&rated).iter()
+            .map(|x| if n > 1.0 { (x - 1.0) / (n - 1.0) } else { 0.5 })
+            .collect()
+    };
+    let mut scores = scores.into_iter();
+    return grades.iter().map(|x| x.and_then(|_| scores.next())).collect();
+}
+
+// Tier sizes follow the pooled share of each grade, with every rater's scale stretched to grade_count grades
+fn assign_grades(ranking: &mut [ConsensusPokemon], gradebooks: &[(String, Gradebook)], grades: &[Vec<Option<i32>>], grade_count: usize) {
+    if grade_count == 0 {
+        return;
+    }
+    let mut histogram = vec![0.0; grade_count];
+    for ((_, gradebook), grades) in gradebooks.iter().zip(grades) {
+        for grade in grades.iter().flatten() {
+            histogram[gradebook.rescale_grade(*grade, grade_count as i32) as usize - 1] += 1.0;
+        }
+    }
+    let total: f64 = histogram.iter().sum();
+
+    // Best pokemon first, so fill the highest grade first
+    let (mut start, mut cumulative) = (0, 0.0);
+    for grade in (1..=grade_count).rev() {
+        cumulative += histogram[grade - 1];
+        let end = ((cumulative / total * ranking.len() as f64).round() as usize).min(ranking.len());
+        for x in &mut ranking[start..end] {
+            x.grade = grade as i32;
+        }
+        start = end;
+    }
+}
+
+fn rater(list: &[Pokemon], name: &str, r: usize, grades: &[Vec<Option<i32>>], scores: &[Vec<Option<f64>>]) -> Rater {
+    // (pokemon, grade, own score, mean of other raters) for pokemon someone else also rated
+    let shared: Vec<(&Pokemon, i32, f64, f64)> = list.iter().enumerate()
+        .filter_map(|(i, pokemon)| {
+            let own = scores[r][i]?;
+            let others: Vec<f64> = scores.iter().enumerate().filter(|x| x.0 != r).filter_map(|x| x.1[i]).collect();
+            if others.is_empty() {
+                return None;
+            }
+            Some((pokemon, grades[r][i].unwrap(), own, mean(&others)))
+        })
+        .collect();
+
+    let (own, others): (Vec<f64>, Vec<f64>) = shared.iter().map(|x| (x.2, x.3)).unzip();
+    let mut contrarian: Vec<Contrarian> = shared.iter()
+        .map(|(pokemon, grade, own, others)| Contrarian {
+            id: pokemon.id(),
+            name: pokemon.name.clone(),
+            grade: *grade,
+            difference: own - others,
+        })
+        .collect();
+    contrarian.sort_by(|x, y| y.difference.abs().total_cmp(&x.difference.abs()));
+    contrarian.truncate(CONTRARIAN_COUNT);
+
+    return Rater {
+        name: name.to_string(),
+        count: grades[r].iter().flatten().count(),
+        agreement: spearman(&own, &others),
+        contrarian,
+    };
+}
@@ -0,0 +1,598 @@
+use crate::pokemon::Pokemon;
+use crate::rules::Rule;
+use crate::regression::{Feature, FeatureImportance, SurprisingGrades};
+use crate::compare::{Agreement, Disagreement};
+use crate::consensus::{Normalisation, Rater, ConsensusPokemon};
+use crate::tierlist::Tier;
+use crate::evolution::{EvolutionPreference, FamilyConsistency};
+use crate::typechart::{defensive_multiplier, stab_multiplier};
+use std::collections::HashMap;
+use std::{hash::Hash, iter::zip};
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+// Number of species in the national dex. Alternate forms are stored after these
+pub const POKEMON_COUNT: usize = 1010;
+// Bump whenever slides.json adds, removes or reorders pokemon
+pub const DATASET_VERSION: u32 = 1;
+pub const GEN_COUNT: usize = 9;
+pub const TYPING_COUNT: usize = 18;
+pub const COLOR_COUNT: usize = 10;
+// Fewer rated pokemon than this in a (generation, type/color) combination are left out of the rankings
+pub const MIN_COMBINATION_COUNT: usize = 5;
+// Combinations listed each way in AnalysisOutput
+pub const COMBINATION_COUNT: usize = 10;
+
+#[derive(Deserialize, Serialize, Debug, EnumIter, PartialEq, Clone, Copy, Hash, Eq)]
+pub enum PTypes { 
+    Normal, Grass, Water, Fire, Electric, 
+    Fighting, Flying, Poison, Ground, Psychic,
+    Rock, Ice, Bug, Dragon, 
+    Ghost, Dark, Steel, Fairy
+}
+
+/// Canonical type chart: multiplier of a move of the attacking type against a single defending type (Gen 6+).
+/// See typechart.rs for whole typings and abilities
+pub fn effectiveness(attack: PTypes, defend: PTypes) -> f64 {
+    let (super_effective, not_very_effective, no_effect) = chart_row(attack);
+    if super_effective.contains(&defend) {
+        return 2.0;
+    }
+    if not_very_effective.contains(&defend) {
+        return 0.5;
+    }
+    if no_effect.contains(&defend) {
+        return 0.0;
+    }
+    return 1.0;
+}
+
+// Types the attacking type is super effective against, not very effective against, and has no effect on
+fn chart_row(attack: PTypes) -> (&'static [PTypes], &'static [PTypes], &'static [PTypes]) {
+    use PTypes::*;
+    return match attack {
+        Normal => (&[], &[Rock, Steel], &[Ghost]),
+        Grass => (&[Water, Ground, Rock], &[Fire, Grass, Poison, Flying, Bug, Dragon, Steel], &[]),
+        Water => (&[Fire, Ground, Rock], &[Water, Grass, Dragon], &[]),
+        Fire => (&[Grass, Ice, Bug, Steel], &[Fire, Water, Rock, Dragon], &[]),
+        Electric => (&[Water, Flying], &[Electric, Grass, Dragon], &[Ground]),
+        Fighting => (&[Normal, Ice, Rock, Dark, Steel], &[Poison, Flying, Psychic, Bug, Fairy], &[Ghost]),
+        Flying => (&[Grass, Fighting, Bug], &[Electric, Rock, Steel], &[]),
+        Poison => (&[Grass, Fairy], &[Poison, Ground, Rock, Ghost], &[Steel]),
+        Ground => (&[Fire, Electric, Poison, Rock, Steel], &[Grass, Bug], &[Flying]),
+        Psychic => (&[Fighting, Poison], &[Psychic, Steel], &[Dark]),
+        Rock => (&[Fire, Ice, Flying, Bug], &[Fighting, Ground, Steel], &[]),
+        Ice => (&[Grass, Ground, Flying, Dragon], &[Fire, Water, Ice, Steel], &[]),
+        Bug => (&[Grass, Psychic, Dark], &[Fire, Fighting, Poison, Flying, Ghost, Steel, Fairy], &[]),
+        Dragon => (&[Dragon], &[Steel], &[Fairy]),
+        Ghost => (&[Psychic, Ghost], &[Dark], &[Normal]),
+        Dark => (&[Psychic, Ghost], &[Fighting, Dark, Fairy], &[]),
+        Steel => (&[Ice, Rock, Fairy], &[Fire, Water, Electric, Steel], &[]),
+        Fairy => (&[Fighting, Dragon, Dark], &[Fire, Poison, Steel], &[]),
+    };
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, EnumIter, PartialEq, Eq, Hash)]
+pub enum PColors {
+    White, Black, Gray, Blue, Red, Green, Pink, Purple, Brown, Yellow
+}
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter)]
+pub enum StatNames { Attack, Defense, SpAtk, SpDef, Speed, Hp } 
+/// Groups of pokemon fans talk about, from slides.json
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, EnumIter, PartialEq, Eq, Hash)]
+pub enum Category {
+    Legendary,
+    Mythical,
+    // Whole starter lines
+    Starter,
+    // Final evolutions with 600 base stats
+    PseudoLegendary,
+    Paradox,
+    UltraBeast,
+    // Revived from fossils, whole lines
+    Fossil,
+    Baby,
+}
+/// Evolutions since the first pokemon in the family. Babies such as Pichu are Basic
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, EnumIter, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum EvolutionStage {
+    #[default]
+    Basic,
+    Stage1,
+    Stage2,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(try_from = "AutofillRulesInput")]
+pub struct AutofillRules {
+    pub rule: Rule,
+    pub grade: i32,
+    // Highest priority wins when several rules match, ties go to the later rule
+    pub priority: i32,
+    pub mode: AutofillMode,
+}
+
+/// Which existing grades a rule may replace
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AutofillMode {
+    Overwrite,
+    // Replace grades set by rules or pairwise mode, keep grades set by hand
+    #[default]
+    KeepManual,
+    UnratedOnly,
+}
+
+impl AutofillMode {
+    pub fn allows(&self, pokemon: &Pokemon) -> bool {
+        return match self {
+            AutofillMode::Overwrite => true,
+            AutofillMode::KeepManual => pokemon.grade.is_none() || !pokemon.grade_source.is_manual(),
+            AutofillMode::UnratedOnly => pokemon.grade.is_none(),
+        };
+    }
+}
+
+// rule may be given as an AST or as text. Rules saved before rule expressions
+// used type_rule1/2 and gen_rule1/2, all of which had to match
+#[derive(Deserialize)]
+struct AutofillRulesInput {
+    rule: Option<RuleInput>,
+    type_rule1: Option<PTypes>,
+    type_rule2: Option<PTypes>,
+    gen_rule1: Option<usize>,
+    gen_rule2: Option<usize>,
+    grade: i32,
+    #[serde(default)]
+    priority: i32,
+    #[serde(default)]
+    mode: AutofillMode,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RuleInput {
+    Text(String),
+    Rule(Rule),
+}
+
+impl TryFrom<AutofillRulesInput> for AutofillRules {
+    type Error = String;
+    fn try_from(input: AutofillRulesInput) -> Result<Self, Self::Error> {
+        let rule = match input.rule {
+            Some(RuleInput::Text(text)) => Rule::parse(&text)?,
+            Some(RuleInput::Rule(rule)) => rule,
+            None => {
+                let mut rules: Vec<Rule> = Vec::new();
+                rules.extend([input.type_rule1, input.type_rule2].into_iter().flatten().map(|typing| Rule::Type { typing }));
+                rules.extend([input.gen_rule1, input.gen_rule2].into_iter().flatten().map(|gen_no| Rule::Gen { gen_no }));
+                Rule::And { rules }
+            }
+        };
+        return Ok(AutofillRules { rule, grade: input.grade, priority: input.priority, mode: input.mode });
+    }
+}
+
+// total, count
+#[derive(Clone, Debug, Serialize)]
+pub struct AvgValue<T> (HashMap<T, (f64, f64)>) where T: Clone + Hash + Eq + Serialize;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct AvgValuePerGrade<T: Clone + Hash + Eq + Serialize> {
+    pub grades: Vec<AvgValue<T>>,
+}
+
+impl<T: Clone + Hash + Eq + Serialize> AvgValuePerGrade<T> {
+    pub fn new(max_grade: usize) -> AvgValuePerGrade<T>{
+        let avg: HashMap<T, (f64, f64)> = HashMap::new();
+        let data: Vec<AvgValue<T>> = vec![AvgValue(avg); max_grade];
+
+        return AvgValuePerGrade {
+            grades: data,
+        };
+    }
+
+    pub fn add_value(&mut self, grade: usize, value_type: T, value: f64) {
+        match self.grades[grade].0.get_mut(&value_type) {
+            Some(g) => {
+                g.0 += value;
+                g.1 += 1.0;
+            },
+            None => { 
+                self.grades[grade].0.insert(value_type, (value, 1.0));
+            }
+        };
+    }
+
+    pub fn get_result(&mut self) -> Vec<HashMap<T, f64>> {
+        let mut output: Vec<HashMap<T, f64>> = Vec::new();
+        let mut average:HashMap<T, f64>;
+        let mut value: f64; 
+
+        for grade in &mut self.grades {
+            average = HashMap::new();
+ 
+            for val in &grade.0 {
+                value = val.1.0 / val.1.1;
+
+                average.insert(val.0.clone(), value);
+            }
+
+            // Mark the min and max values with a - or +
+            // Used by frontend to color these numbers
+            // average = average.iter()
+            //     .map(|x| if *x.1 == curr_min.to_string() {
+            //         (x.0.clone(), format!("-{}", x.1))} else { (x.0.clone(), x.1.clone()) })
+            //     .map(|x| if x.1 == curr_max.to_string() {
+            //         (x.0.clone(), format!("+{}", x.1))} else { (x.0.clone(), x.1) })
+            //     .collect::<HashMap<T, String>>();
+
+            output.push(average);
+        }
+        return output;
+    }
+}
+
+
+
+/// Average grade per exact typing. Unlike typing_average, Fire/Flying and Fire are separate cells
+#[derive(Debug, Serialize, Clone)]
+pub struct TypeMatrix {
+    // Row and column order
+    pub types: Vec<PTypes>,
+    // [primary][secondary] average 0-indexed grade, NaN if there are no pokemon.
+    // Single types are on the diagonal
+    pub averages: Vec<Vec<f64>>,
+    pub counts: Vec<Vec<usize>>,
+}
+
+impl TypeMatrix {
+    pub fn new() -> TypeMatrix {
+        return TypeMatrix {
+            types: PTypes::iter().collect(),
+            averages: vec![vec![0.0; TYPING_COUNT]; TYPING_COUNT],
+            counts: vec![vec![0; TYPING_COUNT]; TYPING_COUNT],
+        };
+    }
+
+    pub fn add(&mut self, typing: &[PTypes], grade: f64) {
+        let index = |t: &PTypes| self.types.iter().position(|x| x == t).unwrap();
+        let primary = index(&typing[0]);
+        let secondary = typing.get(1).map_or(primary, index);
+        // Totals until get_result
+        self.averages[primary][secondary] += grade;
+        self.counts[primary][secondary] += 1;
+    }
+
+    pub fn get_result(mut self) -> TypeMatrix {
+        for (row, counts) in self.averages.iter_mut().zip(&self.counts) {
+            for (total, count) in row.iter_mut().zip(counts) {
+                *total /= *count as f64;
+            }
+        }
+        return self;
+    }
+}
+
+impl Default for TypeMatrix {
+    fn default() -> TypeMatrix {
+        return TypeMatrix::new();
+    }
+}
+
+/// Average grade per generation and category (type or color)
+#[derive(Debug, Serialize, Clone)]
+pub struct CrossTab<T: Serialize> {
+    pub columns: Vec<T>,
+    // [gen - 1][column] average 0-indexed grade, NaN if there are no pokemon
+    pub averages: Vec<Vec<f64>>,
+    pub counts: Vec<Vec<usize>>,
+}
+
+impl<T: Serialize + PartialEq> CrossTab<T> {
+    pub fn new(columns: Vec<T>) -> CrossTab<T> {
+        let width = columns.len();
+        return CrossTab {
+            columns,
+            averages: vec![vec![0.0; width]; GEN_COUNT],
+            counts: vec![vec![0; width]; GEN_COUNT],
+        };
+    }
+
+    pub fn add(&mut self, gen_no: usize, column: &T, grade: f64) {
+        let j = self.columns.iter().position(|x| x == column).unwrap();
+        // Totals until get_result
+        self.averages[gen_no - 1][j] += grade;
+        self.counts[gen_no - 1][j] += 1;
+    }
+
+    pub fn get_result(mut self) -> CrossTab<T> {
+        for (row, counts) in self.averages.iter_mut().zip(&self.counts) {
+            for (total, count) in row.iter_mut().zip(counts) {
+                *total /= *count as f64;
+            }
+        }
+        return self;
+    }
+
+    /// Cells with at least min_count pokemon
+    pub fn combinations(&self, min_count: usize, feature: impl Fn(&T) -> Feature) -> Vec<Combination> {
+        let mut combinations: Vec<Combination> = Vec::new();
+        for (i, (averages, counts)) in self.averages.iter().zip(&self.counts).enumerate() {
+            for (j, column) in self.columns.iter().enumerate().filter(|(j, _)| counts[*j] >= min_count) {
+                combinations.push(Combination { gen_no: i + 1, feature: feature(column), average: averages[j], count: counts[j] });
+            }
+        }
+        return combinations;
+    }
+}
+
+/// e.g. Poison types from Gen 3
+#[derive(Debug, Serialize, Clone)]
+pub struct Combination {
+    pub gen_no: usize,
+    // Feature::Typing or Feature::Color
+    pub feature: Feature,
+    // 0-indexed grade
+    pub average: f64,
+    pub count: usize,
+}
+
+/// Average type matchups of pokemon with one grade, see typechart.rs
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct MatchupSummary {
+    pub count: usize,
+    // Defensive: attacking types that are super effective against the pokemon,
+    // not very effective (including 1/4) and that have no effect, including abilities
+    pub weaknesses: f64,
+    pub resistances: f64,
+    pub immunities: f64,
+    // Offensive: defending types at least one of the pokemon's own types (STAB) hits super effectively,
+    // and those none of them hit for neutral damage or better
+    pub super_effective: f64,
+    pub walled_by: f64,
+}
+
+impl MatchupSummary {
+    pub fn add(&mut self, pokemon: &Pokemon) {
+        let flag = |x: bool| if x { 1.0 } else { 0.0 };
+        // Totals until get_result
+        for other in PTypes::iter() {
+            let defense = defensive_multiplier(other, pokemon);
+            self.weaknesses += flag(defense > 1.0);
+            self.resistances += flag(defense > 0.0 && defense < 1.0);
+            self.immunities += flag(defense == 0.0);
+
+            let offense = stab_multiplier(&pokemon.typing, &[other]);
+            self.super_effective += flag(offense > 1.0);
+            self.walled_by += flag(offense < 1.0);
+        }
+        self.count += 1;
+    }
+
+    pub fn get_result(mut self) -> MatchupSummary {
+        let n = self.count as f64;
+        for value in [&mut self.weaknesses, &mut self.resistances, &mut self.immunities, &mut self.super_effective, &mut self.walled_by] {
+            *value /= n;
+        }
+        return self;
+    }
+}
+
+/// Average grade of a category on scales that do not depend on the number of grades,
+/// so gradebooks with different grade scales can be compared
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct NormalisedScore {
+    // Mean grade where the lowest grade is 0 and the highest is 1
+    pub score: f64,
+    // Mean percentile rank (0-100) of the category's pokemon among all rated pokemon
+    pub percentile: f64,
+}
+
+impl NormalisedScore {
+    /// percentiles: percentile rank of each (0-indexed) grade, see grade_percentiles
+    pub fn new(distribution: &Distribution, percentiles: &[f64]) -> NormalisedScore {
+        let grades = distribution.histogram.len();
+        // A single grade counts as the highest, like Gradebook::normalise
+        let percentile = zip(&distribution.histogram, percentiles).map(|(c, p)| *c as f64 * p).sum::<f64>() / distribution.count as f64;
+        return NormalisedScore {
+            score: if grades > 1 { distribution.mean / (grades - 1) as f64 } else { distribution.mean + 1.0 },
+            percentile,
+        };
+    }
+}
+
+/// Percentile rank of each grade in histogram: share of pokemon graded lower, plus half of those tied
+pub fn grade_percentiles(histogram: &[usize]) -> Vec<f64> {
+    let total: usize = histogram.iter().sum();
+    let mut below = 0;
+    return histogram.iter()
+        .map(|c| {
+            let percentile = (below as f64 + *c as f64 / 2.0) / total as f64 * 100.0;
+            below += c;
+            percentile
+        })
+        .collect();
+}
+
+// Two-sided 95% critical values of Student's t for 1..=30 degrees of freedom
+const T_95: [f64; 30] = [
+    12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228,
+    2.201, 2.179, 2.160, 2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086,
+    2.080, 2.074, 2.069, 2.064, 2.060, 2.056, 2.052, 2.048, 2.045, 2.042,
+];
+
+/// Spread of the (0-indexed) grades in a category.
+/// Statistics that need more pokemon than there are are NaN
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct Distribution {
+    // Number of pokemon with each grade
+    pub histogram: Vec<usize>,
+    pub count: usize,
+    pub mean: f64,
+    pub median: f64,
+    // Sample standard deviation
+    pub std_dev: f64,
+    // 95% confidence interval of the mean
+    pub ci_low: f64,
+    pub ci_high: f64,
+}
+
+impl Distribution {
+    pub fn from_histogram(histogram: Vec<usize>) -> Distribution {
+        let count: usize = histogram.iter().sum();
+        let n = count as f64;
+        let mean = histogram.iter().enumerate().map(|(g, c)| (g * c) as f64).sum::<f64>() / n;
+        let variance = histogram.iter().enumerate()
+            .map(|(g, c)| (g as f64 - mean).powi(2) * *c as f64)
+            .sum::<f64>() / (n - 1.0);
+        let std_dev = if count > 1 { variance.sqrt() } else { f64::NAN };
+
+        // Grade at sorted position i
+        let nth = |i: usize| {
+            let mut seen = 0;
+            return histogram.iter().position(|c| { seen += c; seen > i }).unwrap_or(0) as f64;
+        };
+        let median = match count {
+            0 => f64::NAN,
+            c if c % 2 == 1 => nth(c / 2),
+            c => (nth(c / 2 - 1) + nth(c / 2)) / 2.0
+        };
+
+        let t = T_95.get(count.saturating_sub(2)).copied().unwrap_or(1.96);
+        let margin = t * std_dev / n.sqrt();
+        return Distribution { histogram, count, mean, median, std_dev, ci_low: mean - margin, ci_high: mean + margin };
+    }
+}
+
+// (rated, unrated)
+#[derive(Debug, Serialize, Default)]
+pub struct Coverage {
+    pub rated: usize,
+    pub unrated: usize,
+    pub gen: Vec<(usize, usize)>,
+    pub typing: Vec<(PTypes, usize, usize)>,
+}
+
+impl Coverage {
+    pub fn new() -> Coverage {
+        return Coverage {
+            rated: 0,
+            unrated: 0,
+            gen: vec![(0, 0); GEN_COUNT],
+            typing: PTypes::iter().map(|x| (x, 0, 0)).collect(),
+        };
+    }
+
+    pub fn add(&mut self, pokemon: &Pokemon, is_rated: bool) {
+        let gen = &mut self.gen[pokemon.gen_no - 1];
+        if is_rated {
+            self.rated += 1;
+            gen.0 += 1;
+        } else {
+            self.unrated += 1;
+            gen.1 += 1;
+        }
+        for typing in self.typing.iter_mut().filter(|x| pokemon.is_typing(&x.0)) {
+            if is_rated {
+                typing.1 += 1;
+            } else {
+                typing.2 += 1;
+            }
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct AnalysisOutput {
+    pub coverage: Coverage,
+    pub perfect_scores: Vec<String>,
+    pub worst_scores: Vec<String>,
+    pub gen_average: Vec<f64>,
+    pub typing_average: Vec<(PTypes, f64)>,
+    pub color_average: Vec<(PColors, f64)>,
+    // Only categories with rated pokemon, best first
+    pub category_average: Vec<(Category, f64)>,
+    pub dual_type_average: f64,
+    pub single_type_average: f64,
+    pub manga_average: Vec<f64>,
+    pub anime_average: Vec<f64>,
+    // Per grade, see Pokemon.evolutions
+    pub evolutions_average: Vec<f64>,
+
+    pub stats_data: Vec<HashMap<StatNames, f64>>,
+    pub matchup_data: Vec<HashMap<PTypes, f64>>,
+    // Per grade, from the type chart
+    pub matchup_summary: Vec<MatchupSummary>,
+    // Per grade, percent of pokemon whose own types hit each defending type super effectively
+    pub stab_coverage_data: Vec<HashMap<PTypes, f64>>,
+
+    // Spread behind the averages above. Types, colors and categories in enum order
+    pub overall_distribution: Distribution,
+    pub gen_distribution: Vec<Distribution>,
+    pub typing_distribution: Vec<(PTypes, Distribution)>,
+    pub color_distribution: Vec<(PColors, Distribution)>,
+    pub category_distribution: Vec<(Category, Distribution)>,
+    pub single_type_distribution: Distribution,
+    pub dual_type_distribution: Distribution,
+    // Grades by first listed type, and by second listed type of dual types. Enum order
+    pub primary_type_distribution: Vec<(PTypes, Distribution)>,
+    pub secondary_type_distribution: Vec<(PTypes, Distribution)>,
+    pub type_matrix: TypeMatrix,
+    // Enum order
+    pub stage_distribution: Vec<(EvolutionStage, Distribution)>,
+    // Pokemon that cannot evolve any further, and pokemon that can
+    pub fully_evolved_distribution: Distribution,
+    pub evolving_distribution: Distribution,
+    pub evolution_preference: EvolutionPreference,
+    pub family_consistency: FamilyConsistency,
+    pub gen_typing: CrossTab<PTypes>,
+    pub gen_color: CrossTab<PColors>,
+    // (generation, type/color) combinations with at least MIN_COMBINATION_COUNT pokemon,
+    // above the overall average (best first) and below it (worst first)
+    pub best_combinations: Vec<Combination>,
+    pub worst_combinations: Vec<Combination>,
+
+    // Averages above as normalised scores, same order
+    pub gen_normalised: Vec<NormalisedScore>,
+    pub typing_normalised: Vec<(PTypes, NormalisedScore)>,
+    pub color_normalised: Vec<(PColors, NormalisedScore)>,
+    pub category_normalised: Vec<(Category, NormalisedScore)>,
+    pub single_type_normalised: NormalisedScore,
+    pub dual_type_normalised: NormalisedScore,
+    pub stage_normalised: Vec<(EvolutionStage, NormalisedScore)>,
+
+    // None if too few pokemon are rated
+    pub feature_importance: Option<FeatureImportance>,
+    pub surprising_grades: Option<SurprisingGrades>,
+}
+
+/// Agreement between two gradebooks, see compare.rs.
+/// Only pokemon rated in both are compared
+#[derive(Debug, Serialize)]
+pub struct ComparisonOutput {
+    pub count: usize,
+    // Rated in only one of the gradebooks
+    pub only_a: usize,
+    pub only_b: usize,
+    pub kendall: f64,
+    pub overall: Agreement,
+    pub gen_agreement: Vec<Agreement>,
+    // Types in enum order
+    pub typing_agreement: Vec<(PTypes, Agreement)>,
+    // Largest normalised difference first
+    pub disagreements: Vec<Disagreement>,
+}
+
+/// Group ranking from several gradebooks, see consensus.rs
+#[derive(Debug, Serialize)]
+pub struct ConsensusOutput {
+    pub normalisation: Normalisation,
+    // In the order the gradebooks were given
+    pub raters: Vec<Rater>,
+    // Pokemon rated by anyone, highest score first
+    pub ranking: Vec<ConsensusPokemon>,
+    // Highest variance first, only pokemon rated by several people
+    pub controversial: Vec<ConsensusPokemon>,
+    pub tiers: Vec<Tier>,
+}
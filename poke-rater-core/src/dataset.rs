@@ -0,0 +1,154 @@
+use crate::data::*;
+use crate::pokemon::*;
+use std::{fmt, collections::{HashMap, HashSet}};
+use serde::Serialize;
+use strum::IntoEnumIterator;
+
+// Same file the frontend ships, so both sides always agree on the dataset
+pub const EMBEDDED_SLIDES: &str = include_str!("../../src/slides.json");
+// Matchup multipliers (%) allowed in slides.json
+pub const MULTIPLIERS: [i32; 6] = [0, 25, 50, 100, 200, 400];
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(tag = "kind")]
+pub enum DatasetError {
+    Parse { message: String },
+    DuplicateId { id: PokemonId },
+    // Gap in the base form dex numbers
+    MissingDexNo { dex_no: usize },
+    // Alternate form whose base species is not in the dataset
+    MissingBaseForm { id: PokemonId },
+    InvalidGeneration { id: PokemonId, gen_no: usize },
+    InvalidTyping { id: PokemonId, typing: Vec<PTypes> },
+    InvalidMultiplier { id: PokemonId, multiplier: i32 },
+    // Type listed under more than one multiplier
+    DuplicateMatchup { id: PokemonId, typing: PTypes },
+    MissingStat { id: PokemonId, stat: StatNames },
+    DuplicateStat { id: PokemonId, stat: StatNames },
+}
+
+impl fmt::Display for DatasetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            DatasetError::Parse { message } => write!(f, "Could not parse dataset: {}", message),
+            DatasetError::DuplicateId { id } => write!(f, "{:?} appears more than once", id),
+            DatasetError::MissingDexNo { dex_no } => write!(f, "No pokemon with dex_no {}", dex_no),
+            DatasetError::MissingBaseForm { id } => write!(f, "{:?} has no base form", id),
+            DatasetError::InvalidGeneration { id, gen_no } =>
+                write!(f, "{:?} has gen_no {}, expected 1..={}", id, gen_no, GEN_COUNT),
+            DatasetError::InvalidTyping { id, typing } =>
+                write!(f, "{:?} has typing {:?}, expected 1 or 2 different types", id, typing),
+            DatasetError::InvalidMultiplier { id, multiplier } =>
+                write!(f, "{:?} has matchup multiplier {}, expected one of {:?}", id, multiplier, MULTIPLIERS),
+            DatasetError::DuplicateMatchup { id, typing } =>
+                write!(f, "{:?} lists {:?} under more than one matchup multiplier", id, typing),
+            DatasetError::MissingStat { id, stat } => write!(f, "{:?} is missing {:?}", id, stat),
+            DatasetError::DuplicateStat { id, stat } => write!(f, "{:?} lists {:?} more than once", id, stat),
+        };
+    }
+}
+
+/// Returned instead of a dataset when validation fails
+#[derive(Debug, Serialize, Clone)]
+pub struct DatasetReport {
+    pub pokemon_count: usize,
+    pub slide_count: usize,
+    pub errors: Vec<DatasetError>,
+}
+
+impl fmt::Display for DatasetReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Dataset has {} errors ({} pokemon, {} slides)",
+            self.errors.len(), self.pokemon_count, self.slide_count)?;
+        for error in &self.errors {
+            writeln!(f, "  {}", error)?;
+        }
+        return Ok(());
+    }
+}
+
+/// Parse and validate slides.json
+pub fn load_slides(json: &str) -> Result<Vec<Vec<Pokemon>>, DatasetReport> {
+    let slides: Vec<Vec<Pokemon>> = serde_json::from_str(json).map_err(|e| DatasetReport {
+        pokemon_count: 0,
+        slide_count: 0,
+        errors: vec![DatasetError::Parse { message: e.to_string() }],
+    })?;
+
+    let errors = validate_slides(&slides);
+    if !errors.is_empty() {
+        return Err(DatasetReport {
+            pokemon_count: slides.iter().map(|x| x.len()).sum(),
+            slide_count: slides.len(),
+            errors,
+        });
+    }
+    return Ok(slides);
+}
+
+pub fn validate_slides(slides: &[Vec<Pokemon>]) -> Vec<DatasetError> {
+    let mut errors: Vec<DatasetError> = Vec::new();
+    let mut ids: HashSet<PokemonId> = HashSet::new();
+
+    for pokemon in slides.iter().flatten() {
+        if !ids.insert(pokemon.id()) {
+            errors.push(DatasetError::DuplicateId { id: pokemon.id() });
+        }
+        errors.extend(validate_pokemon(pokemon));
+    }
+
+    // Base forms must be 1..=max dex_no, alternate forms need a base form
+    let base: HashSet<usize> = ids.iter().filter(|x| x.form.is_none()).map(|x| x.dex_no).collect();
+    let max_dex_no = base.iter().max().copied().unwrap_or(0);
+    for dex_no in (1..=max_dex_no).filter(|x| !base.contains(x)) {
+        errors.push(DatasetError::MissingDexNo { dex_no });
+    }
+    let mut forms: Vec<&PokemonId> = ids.iter().filter(|x| x.form.is_some() && !base.contains(&x.dex_no)).collect();
+    forms.sort();
+    for id in forms {
+        errors.push(DatasetError::MissingBaseForm { id: id.clone() });
+    }
+    return errors;
+}
+
+fn validate_pokemon(pokemon: &Pokemon) -> Vec<DatasetError> {
+    let mut errors: Vec<DatasetError> = Vec::new();
+    let id = pokemon.id();
+
+    if pokemon.gen_no < 1 || pokemon.gen_no > GEN_COUNT {
+        errors.push(DatasetError::InvalidGeneration { id: id.clone(), gen_no: pokemon.gen_no });
+    }
+
+    let typing = &pokemon.typing;
+    if typing.is_empty() || typing.len() > 2 || (typing.len() == 2 && typing[0] == typing[1]) {
+        errors.push(DatasetError::InvalidTyping { id: id.clone(), typing: typing.clone() });
+    }
+
+    // Sorted so errors come out in the same order every time
+    let mut matchups: Vec<(&i32, &Vec<PTypes>)> = pokemon.matchups.iter().collect();
+    matchups.sort_by_key(|x| x.0);
+    let mut seen: HashSet<PTypes> = HashSet::new();
+    for (multiplier, types) in matchups {
+        if !MULTIPLIERS.contains(multiplier) {
+            errors.push(DatasetError::InvalidMultiplier { id: id.clone(), multiplier: *multiplier });
+        }
+        for typing in types {
+            if !seen.insert(*typing) {
+                errors.push(DatasetError::DuplicateMatchup { id: id.clone(), typing: *typing });
+            }
+        }
+    }
+
+    let mut stats: HashMap<StatNames, usize> = HashMap::new();
+    for stat in &pokemon.stats {
+        *stats.entry(stat.0).or_insert(0) += 1;
+    }
+    for stat in StatNames::iter() {
+        match stats.get(&stat) {
+            None => errors.push(DatasetError::MissingStat { id: id.clone(), stat }),
+            Some(count) if *count > 1 => errors.push(DatasetError::DuplicateStat { id: id.clone(), stat }),
+            _ => ()
+        };
+    }
+    return errors;
+}
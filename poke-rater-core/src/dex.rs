@@ -0,0 +1,277 @@
+use crate::data::*;
+use crate::pokemon::*;
+use crate::gradebook::*;
+use crate::analysis::run_analysis;
+use crate::autofill::{self, AutofillChange, AutofillReport};
+use crate::dataset::*;
+use crate::pairwise::PairwiseSession;
+use crate::journal::*;
+use crate::evolution::assign_evolutions;
+use std::{collections::HashMap, path::Path};
+
+pub type SlideOrder = Vec<Vec<PokemonId>>;
+
+/// Pokemon list sorted by PokemonId, the slides they are graded in, 
+/// and the header of the open gradebook
+#[derive(Debug, Default)]
+pub struct Dex {
+    pub list: Vec<Pokemon>,
+    pub slides: Option<SlideOrder>,
+    pub gradebook: Option<Gradebook>,
+    // Changes made by the last autofill
+    pub last_autofill: Option<Vec<GradeChange>>,
+    // Undo/redo history, saved with the gradebook
+    pub journal: Journal,
+}
+
+impl Dex {
+    pub fn new() -> Dex {
+        return Dex::default();
+    }
+
+    /// Parse and validate slides.json
+    pub fn load(json: &str) -> Result<Dex, DatasetReport> {
+        return Ok(Dex::from_slides(load_slides(json)?));
+    }
+
+    /// Dataset compiled into the binary
+    pub fn embedded() -> Result<Dex, DatasetReport> {
+        return Dex::load(EMBEDDED_SLIDES);
+    }
+
+    /// Build from pokemon grouped into slides
+    pub fn from_slides(slides: Vec<Vec<Pokemon>>) -> Dex {
+        let mut dex = Dex::new();
+        dex.init(slides);
+        return dex;
+    }
+
+    /// Receives pokemon in slide order (one evolution family per slide), sorts them into dex order.
+    /// Returns slide order. Only the first call has any effect
+    pub fn init(&mut self, mut slides: Vec<Vec<Pokemon>>) -> SlideOrder {
+        if let Some(s) = &self.slides {
+            return s.clone();
+        }
+        assign_evolutions(&mut slides);
+
+        let mut slide_order: SlideOrder = Vec::with_capacity(slides.len());
+        let mut current_slide: Vec<PokemonId>;
+
+        for slide in slides {
+            current_slide = Vec::with_capacity(slide.len());
+            
+            for pokemon in slide {
+                current_slide.push(pokemon.id());
+                self.list.push(pokemon);
+            }
+
+            slide_order.push(current_slide);
+        }
+        self.list.sort();
+        group_forms(&mut slide_order);
+        self.slides = Some(slide_order.clone());
+        return slide_order;
+    }
+
+    pub fn get(&self, id: &PokemonId) -> Option<&Pokemon> {
+        return find_pokemon(&self.list, id).map(|i| &self.list[i]);
+    }
+
+    pub fn get_mut(&mut self, id: &PokemonId) -> Option<&mut Pokemon> {
+        return find_pokemon(&self.list, id).map(|i| &mut self.list[i]);
+    }
+
+    /// Grade set by hand. None marks pokemon as unrated. Returns the pokemon if it exists
+    pub fn set_grade(&mut self, id: &PokemonId, grade: Option<i32>) -> Option<&Pokemon> {
+        return self.set_grade_from(id, grade, GradeSource::Manual);
+    }
+
+    pub fn set_grade_from(&mut self, id: &PokemonId, grade: Option<i32>, source: GradeSource) -> Option<&Pokemon> {
+        self.get(id)?;
+        self.record_grades(vec![(id.clone(), grade, source)], source);
+        return self.get(id);
+    }
+
+    // Apply grades as a single journal entry. Unknown pokemon and grades that
+    // do not change are skipped. Returns changes made
+    fn record_grades(&mut self, grades: Vec<(PokemonId, Option<i32>, GradeSource)>, source: GradeSource) -> Vec<GradeChange> {
+        let mut changes: Vec<GradeChange> = Vec::new();
+        for (id, grade, grade_source) in grades {
+            let Some(pokemon) = self.get_mut(&id) else { continue };
+            if pokemon.grade == grade && pokemon.grade_source == grade_source {
+                continue;
+            }
+            changes.push(GradeChange {
+                id,
+                grade,
+                source: grade_source,
+                previous: pokemon.grade,
+                previous_source: pokemon.grade_source,
+            });
+            pokemon.grade = grade;
+            pokemon.grade_source = grade_source;
+        }
+        if !changes.is_empty() {
+            self.journal.record(JournalEntry { time: now(), source, changes: changes.clone() });
+        }
+        return changes;
+    }
+
+    /// Revert the last grade change. Returns the reverted entry
+    pub fn undo(&mut self) -> Result<JournalEntry, String> {
+        return self.journal.undo(&mut self.list).cloned().ok_or(String::from("Nothing to undo"));
+    }
+
+    /// Apply the last undone grade change again
+    pub fn redo(&mut self) -> Result<JournalEntry, String> {
+        return self.journal.redo(&mut self.list).cloned().ok_or(String::from("Nothing to redo"));
+    }
+
+    /// Applied grade changes, most recent first
+    pub fn history(&self) -> Vec<JournalEntry> {
+        return self.journal.history().cloned().collect();
+    }
+
+    /// Unrated pokemon in slide order (dex order if slides are not loaded)
+    pub fn unrated(&self) -> Vec<PokemonId> {
+        let unrated = |id: &PokemonId| self.get(id).is_some_and(|x| x.grade.is_none());
+
+        return match &self.slides {
+            Some(slides) => slides.iter().flatten().filter(|x| unrated(x)).cloned().collect(),
+            None => self.list.iter().filter(|x| x.grade.is_none()).map(|x| x.id()).collect()
+        };
+    }
+
+    pub fn autofill(&mut self, rules: &[AutofillRules]) -> AutofillReport {
+        let changes = self.preview_autofill(rules);
+        let report = AutofillReport::new(&changes, rules.len());
+        let grades = changes.into_iter().map(|x| (x.id, Some(x.new), GradeSource::Autofill)).collect();
+        self.last_autofill = Some(self.record_grades(grades, GradeSource::Autofill));
+        return report;
+    }
+
+    /// Grades autofill would change, without changing them
+    pub fn preview_autofill(&self, rules: &[AutofillRules]) -> Vec<AutofillChange> {
+        return autofill::autofill_changes(&self.list, rules);
+    }
+
+    /// Restore grades from before the last autofill.
+    /// Grades changed since are left alone. Returns number of grades restored
+    pub fn undo_autofill(&mut self) -> Result<usize, String> {
+        let backup = self.last_autofill.take().ok_or("Nothing to undo")?;
+        let grades = backup.into_iter()
+            .filter(|x| self.get(&x.id).is_some_and(|p| p.grade == x.grade && p.grade_source == x.source))
+            .map(|x| (x.id, x.previous, x.previous_source))
+            .collect();
+        return Ok(self.record_grades(grades, GradeSource::Autofill).len());
+    }
+
+    /// Clear all grades and start a new gradebook
+    pub fn new_gradebook(&mut self, grade_labels: Vec<String>) {
+        for pokemon in self.list.iter_mut() {
+            pokemon.grade = None;
+        }
+        self.last_autofill = None;
+        self.journal = Journal::new();
+        self.gradebook = Some(Gradebook::new(grade_labels));
+    }
+
+    /// Load gradebook (.gradebook.json or legacy .csv) into list.
+    /// Returns ids in the gradebook that are not in the dataset
+    pub fn load_gradebook(&mut self, path: &Path) -> Result<Vec<PokemonId>, String> {
+        let gradebook = Gradebook::load(path, &self.list)?;
+        let missing = gradebook.apply(&mut self.list);
+        self.last_autofill = None;
+        self.journal = gradebook.journal.clone();
+        self.gradebook = Some(gradebook);
+        return Ok(missing);
+    }
+
+    /// Write grades to path, creating a gradebook if none is open
+    pub fn save_gradebook(&mut self, path: &Path, grade_labels: Vec<String>, cursor: Option<PokemonId>) -> Result<(), String> {
+        let gradebook = self.gradebook.get_or_insert_with(|| Gradebook::new(grade_labels.clone()));
+        gradebook.max_grade = grade_labels.len() as i32;
+        gradebook.grade_labels = grade_labels;
+        gradebook.update(&self.list, cursor);
+        gradebook.journal = self.journal.clone();
+        return gradebook.save(path);
+    }
+
+    /// Convert grades in list and the open gradebook to grade_labels.
+    /// Clears undo history, see Gradebook::rescale
+    pub fn rescale_gradebook(&mut self, grade_labels: Vec<String>) -> Result<&Gradebook, String> {
+        let gradebook = self.gradebook.as_mut().ok_or("No gradebook is open")?;
+        gradebook.update(&self.list, gradebook.cursor.clone());
+        let rescaled = gradebook.rescale(grade_labels)?;
+        rescaled.apply(&mut self.list);
+        self.last_autofill = None;
+        self.journal = Journal::new();
+        return Ok(self.gradebook.insert(rescaled));
+    }
+
+    /// Next pair to compare in pairwise mode
+    pub fn next_pair(&self) -> Option<(PokemonId, PokemonId)> {
+        return match &self.gradebook {
+            Some(g) => g.pairwise.next_pair(&self.list),
+            None => PairwiseSession::new().next_pair(&self.list)
+        };
+    }
+
+    /// Record a head-to-head pick in the open gradebook
+    pub fn record_preference(&mut self, winner: &PokemonId, loser: &PokemonId) -> Result<(), String> {
+        if winner == loser {
+            return Err(String::from("A pokemon cannot be compared with itself"));
+        }
+        for id in [winner, loser] {
+            if self.get(id).is_none() {
+                return Err(format!("{:?} is not in the dataset", id));
+            }
+        }
+        let gradebook = self.gradebook.as_mut().ok_or("No gradebook is open")?;
+        gradebook.pairwise.record(winner, loser);
+        return Ok(());
+    }
+
+    /// Replace grades of every compared pokemon with grades derived from pairwise ratings.
+    /// Returns number of pokemon graded
+    pub fn apply_pairwise(&mut self) -> Result<usize, String> {
+        let gradebook = self.gradebook.as_ref().ok_or("No gradebook is open")?;
+        let grades = gradebook.pairwise.to_grades(gradebook.max_grade);
+
+        let count = grades.len();
+        self.record_grades(grades.into_iter().map(|(id, grade)| (id, Some(grade), GradeSource::Pairwise)).collect(), GradeSource::Pairwise);
+        return Ok(count);
+    }
+
+    pub fn analyze(&self, num_grades: i32) -> Result<AnalysisOutput, String> {
+        if num_grades < 1 {
+            return Err(format!("Number of grades must be at least 1, not {}", num_grades));
+        }
+        return Ok(run_analysis(&self.list, num_grades));
+    }
+}
+
+/// Move alternate forms into the slide holding their base species
+/// e.g. Alolan Ninetales is shown next to Ninetales
+pub fn group_forms(slides: &mut Vec<Vec<PokemonId>>) {
+    let mut base_slide: HashMap<usize, usize> = HashMap::new();
+    for (i, slide) in slides.iter().enumerate() {
+        for id in slide.iter().filter(|x| x.form.is_none()) {
+            base_slide.insert(id.dex_no, i);
+        }
+    }
+
+    for i in 0..slides.len() {
+        let (stay, moved): (Vec<PokemonId>, Vec<PokemonId>) = slides[i].drain(..)
+            .partition(|x| x.form.is_none() || base_slide.get(&x.dex_no).is_none_or(|s| *s == i));
+        slides[i] = stay;
+
+        for id in moved {
+            let target = &mut slides[base_slide[&id.dex_no]];
+            // Insert after the last entry sharing its dex_no
+            let pos = target.iter().rposition(|x| x.dex_no == id.dex_no).map_or(target.len(), |p| p + 1);
+            target.insert(pos, id);
+        }
+    }
+    slides.retain(|x| !x.is_empty());
+}
@@ -0,0 +1,210 @@
+use crate::data::*;
+use crate::pokemon::*;
+use crate::stats::mean;
+use std::{cmp::Reverse, collections::{BTreeMap, HashMap, HashSet}};
+use serde::Serialize;
+use strum::IntoEnumIterator;
+
+// Pre-evolutions that are not the pokemon before it in its slide: branching lines, pokemon that share
+// a slide without being related, and Eevee's evolutions, which slides.json splits up. None starts a family
+const PRE_EVOLUTIONS: &[(usize, Option<usize>)] = &[
+    // Eevee
+    (134, Some(133)), (135, Some(133)), (136, Some(133)), (196, Some(133)),
+    (197, Some(133)), (470, Some(133)), (471, Some(133)), (700, Some(133)),
+    // Branches
+    (182, Some(44)), (186, Some(61)), (199, Some(79)), (107, Some(236)), (237, Some(236)),
+    (863, Some(52)), (900, Some(123)), (903, Some(215)), (980, Some(194)),
+    (268, Some(265)), (475, Some(281)), (292, Some(290)), (368, Some(366)), (478, Some(361)),
+    (414, Some(412)), (867, Some(562)), (792, Some(790)), (842, Some(840)), (937, Some(935)),
+    // Same slide, not related
+    (312, None), (314, None),
+];
+// Families listed in FamilyConsistency.widest
+pub const FAMILY_COUNT: usize = 10;
+// Longer chains of pre-evolutions than this can only come from a loop in a custom dataset
+const MAX_CHAIN: usize = 10;
+
+/// Fill in family, stage, fully_evolved and evolutions. Each slide holds an evolution family in order,
+/// so a pokemon evolves from the base form before it unless PRE_EVOLUTIONS says otherwise.
+/// Alternate forms share their base form's line
+pub fn assign_evolutions(slides: &mut [Vec<Pokemon>]) {
+    let mut pre_evolutions: HashMap<usize, Option<usize>> = HashMap::new();
+    for slide in slides.iter() {
+        let mut previous = None;
+        for pokemon in slide.iter().filter(|x| x.is_base_form()) {
+            pre_evolutions.insert(pokemon.dex_no, previous);
+            previous = Some(pokemon.dex_no);
+        }
+    }
+    pre_evolutions.extend(PRE_EVOLUTIONS.iter().copied());
+
+    // dex_no: (family, evolutions since the family's Basic pokemon)
+    let mut lines: HashMap<usize, (usize, usize)> = HashMap::new();
+    for dex_no in pre_evolutions.keys() {
+        let (mut current, mut depth) = (*dex_no, 0);
+        while let Some(Some(previous)) = pre_evolutions.get(&current) {
+            if depth == MAX_CHAIN {
+                break;
+            }
+            current = *previous;
+            depth += 1;
+        }
+        lines.insert(*dex_no, (current, depth));
+    }
+
+    let mut evolutions: HashMap<usize, usize> = HashMap::new();
+    for (family, depth) in lines.values() {
+        let longest = evolutions.entry(*family).or_insert(0);
+        *longest = (*longest).max(*depth);
+    }
+    let evolves: HashSet<usize> = pre_evolutions.values().flatten().copied().collect();
+
+    for pokemon in slides.iter_mut().flatten() {
+        let (family, depth) = lines.get(&pokemon.dex_no).copied().unwrap_or((pokemon.dex_no, 0));
+        pokemon.family = family;
+        pokemon.stage = EvolutionStage::iter().nth(depth).unwrap_or(EvolutionStage::Stage2);
+        pokemon.fully_evolved = !evolves.contains(&pokemon.dex_no);
+        pokemon.evolutions = evolutions.get(&family).copied().unwrap_or(0);
+    }
+}
+
+/// Pokemon grouped by family, in family order
+pub fn families(list: &[Pokemon]) -> Vec<Vec<&Pokemon>> {
+    let mut families: BTreeMap<usize, Vec<&Pokemon>> = BTreeMap::new();
+    for pokemon in list {
+        families.entry(pokemon.family).or_default().push(pokemon);
+    }
+    return families.into_values().collect();
+}
+
+/// Grades within evolution families (0-indexed)
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct EvolutionPreference {
+    // Families with a rated Basic pokemon and a rated fully evolved pokemon
+    pub families: usize,
+    pub prefer_basic: usize,
+    pub prefer_final: usize,
+    pub ties: usize,
+    // Mean of (fully evolved grade - Basic grade) over those families, positive if final evolutions are preferred.
+    // Branches and alternate forms are averaged first
+    pub mean_difference: f64,
+    // Mean of (grade - family's mean grade) per stage, over families with 2+ rated pokemon. Enum order
+    pub stage_deviation: Vec<(EvolutionStage, f64)>,
+}
+
+// Families with 2+ rated pokemon, as (pokemon, 0-indexed grade) in stage order
+fn rated_families(list: &[Pokemon], num_grades: i32) -> Vec<Vec<(&Pokemon, f64)>> {
+    let grade = |x: &Pokemon| x.grade.filter(|g| *g >= 1 && *g <= num_grades).map(|g| (g - 1) as f64);
+    return families(list).into_iter()
+        .map(|family| {
+            let mut rated: Vec<(&Pokemon, f64)> = family.into_iter().filter_map(|x| Some((x, grade(x)?))).collect();
+            rated.sort_by_key(|x| x.0.stage);
+            rated
+        })
+        .filter(|x| x.len() > 1)
+        .collect();
+}
+
+/// Compare grades of pokemon in the same family, so that whether a family is liked at all does not count
+pub fn evolution_preference(list: &[Pokemon], num_grades: i32) -> EvolutionPreference {
+    let mut differences: Vec<f64> = Vec::new();
+    let mut deviations: HashMap<EvolutionStage, Vec<f64>> = HashMap::new();
+    for rated in rated_families(list, num_grades) {
+        let family_mean = mean(&rated.iter().map(|x| x.1).collect::<Vec<f64>>());
+        for (pokemon, grade) in &rated {
+            deviations.entry(pokemon.stage).or_default().push(grade - family_mean);
+        }
+
+        let basic: Vec<f64> = rated.iter().filter(|x| x.0.stage == EvolutionStage::Basic && !x.0.fully_evolved).map(|x| x.1).collect();
+        let fully_evolved: Vec<f64> = rated.iter().filter(|x| x.0.fully_evolved && x.0.stage != EvolutionStage::Basic).map(|x| x.1).collect();
+        if !basic.is_empty() && !fully_evolved.is_empty() {
+            differences.push(mean(&fully_evolved) - mean(&basic));
+        }
+    }
+
+    return EvolutionPreference {
+        families: differences.len(),
+        prefer_basic: differences.iter().filter(|x| **x < 0.0).count(),
+        prefer_final: differences.iter().filter(|x| **x > 0.0).count(),
+        ties: differences.iter().filter(|x| **x == 0.0).count(),
+        mean_difference: mean(&differences),
+        stage_deviation: EvolutionStage::iter()
+            .map(|x| (x, deviations.get(&x).map_or(f64::NAN, |d| mean(d))))
+            .collect(),
+    };
+}
+
+/// Grades in one family
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct FamilyGrades {
+    // dex_no and name of the family's Basic pokemon
+    pub family: usize,
+    pub name: String,
+    // Rated pokemon and their grades, in stage order
+    pub members: Vec<(String, i32)>,
+    // Highest grade - lowest grade
+    pub spread: f64,
+    // Mean grade of the last rated stage - mean grade of the first, NaN if only one stage is rated
+    pub trend: f64,
+}
+
+/// How much grades vary inside evolution families with 2+ rated pokemon
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct FamilyConsistency {
+    pub families: usize,
+    pub mean_spread: f64,
+    // Largest spread first, then largest change from first to last stage
+    pub widest: Vec<FamilyGrades>,
+    // Every member graded the same, largest families first
+    pub uniform: Vec<FamilyGrades>,
+    // Mean trend over families with 2+ rated stages, and their mean (0-indexed) grade per stage. Enum order
+    pub mean_trend: f64,
+    pub stage_average: Vec<(EvolutionStage, f64)>,
+}
+
+/// Families that split the rater (loved the Basic pokemon, hated the final evolution) and families rated alike.
+/// Families are the slides, see assign_evolutions
+pub fn family_consistency(list: &[Pokemon], num_grades: i32) -> FamilyConsistency {
+    let mut families: Vec<FamilyGrades> = Vec::new();
+    let mut stage_grades: HashMap<EvolutionStage, Vec<f64>> = HashMap::new();
+    for rated in rated_families(list, num_grades) {
+        let grades: Vec<f64> = rated.iter().map(|x| x.1).collect();
+        let spread = grades.iter().copied().fold(f64::MIN, f64::max) - grades.iter().copied().fold(f64::MAX, f64::min);
+
+        let (first, last) = (rated[0].0.stage, rated[rated.len() - 1].0.stage);
+        let stage_mean = |stage: EvolutionStage| mean(&rated.iter().filter(|x| x.0.stage == stage).map(|x| x.1).collect::<Vec<f64>>());
+        let trend = if first == last { f64::NAN } else { stage_mean(last) - stage_mean(first) };
+        if first != last {
+            for (pokemon, grade) in &rated {
+                stage_grades.entry(pokemon.stage).or_default().push(*grade);
+            }
+        }
+
+        let basic = list.iter().find(|x| x.dex_no == rated[0].0.family && x.is_base_form()).unwrap_or(rated[0].0);
+        families.push(FamilyGrades {
+            family: basic.dex_no,
+            name: basic.name.clone(),
+            members: rated.iter().map(|x| (x.0.name.clone(), x.1 as i32 + 1)).collect(),
+            spread,
+            trend,
+        });
+    }
+
+    let trends: Vec<f64> = families.iter().map(|x| x.trend).filter(|x| !x.is_nan()).collect();
+    let mut uniform: Vec<FamilyGrades> = families.iter().filter(|x| x.spread == 0.0).cloned().collect();
+    uniform.sort_by_key(|x| Reverse(x.members.len()));
+    let mut widest: Vec<FamilyGrades> = families.iter().filter(|x| x.spread > 0.0).cloned().collect();
+    widest.sort_by(|x, y| y.spread.total_cmp(&x.spread).then(y.trend.abs().total_cmp(&x.trend.abs())));
+    widest.truncate(FAMILY_COUNT);
+
+    return FamilyConsistency {
+        families: families.len(),
+        mean_spread: mean(&families.iter().map(|x| x.spread).collect::<Vec<f64>>()),
+        widest,
+        uniform,
+        mean_trend: mean(&trends),
+        stage_average: EvolutionStage::iter()
+            .map(|x| (x, stage_grades.get(&x).map_or(f64::NAN, |g| mean(g))))
+            .collect(),
+    };
+}
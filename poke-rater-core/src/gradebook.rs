@@ -0,0 +1,235 @@
+use crate::data::*;
+use crate::pokemon::*;
+use crate::pairwise::PairwiseSession;
+use crate::journal::Journal;
+use std::{fs, path::{Path, PathBuf}, iter::zip, time::{SystemTime, UNIX_EPOCH}};
+use serde::{Deserialize, Serialize};
+
+pub const GRADEBOOK_VERSION: u32 = 1;
+pub const GRADEBOOK_EXTENSION: &str = "gradebook.json";
+pub const LEGACY_EXTENSION: &str = "csv";
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct GradeEntry {
+    #[serde(flatten)]
+    pub id: PokemonId,
+    pub grade: i32,
+    #[serde(default)]
+    pub source: GradeSource,
+}
+
+/// Saved gradebook document.
+/// Grades are keyed by PokemonId, so changes to the dataset cannot shift them
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Gradebook {
+    pub version: u32,
+    pub dataset_version: u32,
+    pub grade_labels: Vec<String>,
+    pub max_grade: i32,
+    // Pokemon the user was last grading
+    pub cursor: Option<PokemonId>,
+    // Unix timestamps (seconds)
+    pub created: u64,
+    pub modified: u64,
+    pub grades: Vec<GradeEntry>,
+    // Head-to-head picks, see pairwise.rs
+    #[serde(default)]
+    pub pairwise: PairwiseSession,
+    // Undo/redo history, see journal.rs
+    #[serde(default)]
+    pub journal: Journal,
+}
+
+impl Gradebook {
+    pub fn new(grade_labels: Vec<String>) -> Gradebook {
+        let time = now();
+        return Gradebook {
+            version: GRADEBOOK_VERSION,
+            dataset_version: DATASET_VERSION,
+            max_grade: grade_labels.len() as i32,
+            grade_labels,
+            cursor: None,
+            created: time,
+            modified: time,
+            grades: Vec::new(),
+            pairwise: PairwiseSession::new(),
+            journal: Journal::new(),
+        };
+    }
+
+    /// Copy grades out of list. Unrated pokemon have no entry
+    pub fn update(&mut self, list: &[Pokemon], cursor: Option<PokemonId>) {
+        self.grades = list.iter()
+            .filter_map(|x| x.grade.map(|grade| GradeEntry { id: x.id(), grade, source: x.grade_source }))
+            .collect();
+        self.cursor = cursor;
+        self.dataset_version = DATASET_VERSION;
+        self.modified = now();
+    }
+
+    /// Copy grades into list, clearing any previous grades.
+    /// Returns entries that do not exist in the dataset
+    pub fn apply(&self, list: &mut [Pokemon]) -> Vec<PokemonId> {
+        let mut missing: Vec<PokemonId> = Vec::new();
+        for pokemon in list.iter_mut() {
+            pokemon.grade = None;
+        }
+        for entry in &self.grades {
+            match find_pokemon(list, &entry.id) {
+                Some(i) => {
+                    list[i].grade = Some(entry.grade);
+                    list[i].grade_source = entry.source;
+                },
+                None => missing.push(entry.id.clone())
+            };
+        }
+        return missing;
+    }
+
+    /// Grade of each pokemon in list, in list order.
+    /// Entries outside 1..=max_grade count as unrated
+    pub fn grades_for(&self, list: &[Pokemon]) -> Vec<Option<i32>> {
+        let mut grades: Vec<Option<i32>> = vec![None; list.len()];
+        for entry in self.grades.iter().filter(|x| x.grade >= 1 && x.grade <= self.max_grade) {
+            if let Some(i) = find_pokemon(list, &entry.id) {
+                grades[i] = Some(entry.grade);
+            }
+        }
+        return grades;
+    }
+
+    /// Grade on a 0 (lowest) to 1 (highest) scale, so gradebooks with different labels can be compared
+    pub fn normalise(&self, grade: i32) -> f64 {
+        if self.max_grade <= 1 {
+            return 1.0;
+        }
+        return (grade - 1) as f64 / (self.max_grade - 1) as f64;
+    }
+
+    /// Grade on a scale of max_grade grades, keeping its relative position
+    pub fn rescale_grade(&self, grade: i32, max_grade: i32) -> i32 {
+        let grade = (self.normalise(grade) * (max_grade - 1) as f64).round() as i32 + 1;
+        return grade.clamp(1, max_grade.max(1));
+    }
+
+    /// Copy with grades converted to grade_labels (e.g. 5 stars to 10 points).
+    /// Undo history refers to the old grades, so it is cleared
+    pub fn rescale(&self, grade_labels: Vec<String>) -> Result<Gradebook, String> {
+        if grade_labels.len() < 2 {
+            return Err(format!("Need at least 2 grade labels, not {}", grade_labels.len()));
+        }
+        let max_grade = grade_labels.len() as i32;
+        let mut gradebook = self.clone();
+        for entry in gradebook.grades.iter_mut() {
+            entry.grade = self.rescale_grade(entry.grade, max_grade);
+        }
+        gradebook.grade_labels = grade_labels;
+        gradebook.max_grade = max_grade;
+        gradebook.journal = Journal::new();
+        gradebook.modified = now();
+        return Ok(gradebook);
+    }
+
+    pub fn from_json(json: &str) -> Result<Gradebook, String> {
+        let gradebook: Gradebook = serde_json::from_str(json)
+            .map_err(|e| format!("Could not parse gradebook: {}", e))?;
+
+        if gradebook.version > GRADEBOOK_VERSION {
+            return Err(format!("Gradebook version {} is newer than supported version {}", 
+                gradebook.version, GRADEBOOK_VERSION));
+        }
+        return Ok(gradebook);
+    }
+
+    pub fn to_json(&self) -> String {
+        return serde_json::to_string_pretty(self).expect("Could not serialize gradebook");
+    }
+
+    /// Migrate csv written by older versions.
+    /// Optional first line is grade labels, the rest are grades in legacy_order.
+    /// Item with "|" char is the cursor. Empty, 0 or unparseable items are unrated
+    pub fn from_legacy_csv(csv: &str, list: &[Pokemon]) -> Gradebook {
+        const CURSOR: char = '|';
+        let lines: Vec<&str> = csv.trim_end().split('\n').collect();
+        let (labels, grades) = match lines.len() {
+            1 => (None, lines[0].to_string()),
+            _ => (Some(lines[0]), lines[1..].join(","))
+        };
+
+        let mut gradebook = Gradebook::new(Vec::new());
+        let mut max_grade = 0;
+
+        // Extra items are ignored, missing items are left ungraded
+        for (i, grade) in zip(legacy_order(list), grades.split(",")) {
+            if grade.contains(CURSOR) {
+                gradebook.cursor = Some(list[i].id());
+            }
+            // Unrated pokemon were written as 0
+            let grade = match grade.trim().replace(CURSOR, "").parse::<i32>() {
+                Ok(g) if g > 0 => g,
+                _ => continue
+            };
+            max_grade = max_grade.max(grade);
+            gradebook.grades.push(GradeEntry { id: list[i].id(), grade, source: GradeSource::Import });
+        }
+
+        // Files without a header used the default 5 numeric grades
+        gradebook.grade_labels = match labels {
+            Some(l) => l.split(",").map(|x| x.trim().to_string()).collect(),
+            None => (1..=max_grade.max(5)).map(|x| x.to_string()).collect()
+        };
+        gradebook.max_grade = gradebook.grade_labels.len() as i32;
+        return gradebook;
+    }
+
+    /// Read .gradebook.json, or migrate a legacy .csv
+    pub fn load(path: &Path, list: &[Pokemon]) -> Result<Gradebook, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+
+        if path.extension().is_some_and(|x| x == LEGACY_EXTENSION) {
+            return Ok(Gradebook::from_legacy_csv(&contents, list));
+        }
+        return Gradebook::from_json(&contents);
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("Could not create {}: {}", dir.display(), e))?;
+        }
+        return fs::write(path, self.to_json())
+            .map_err(|e| format!("Could not write {}: {}", path.display(), e));
+    }
+}
+
+/// Gradebooks saved in dir as (name, path), sorted by name.
+/// A legacy .csv is only listed if there is no .gradebook.json with the same name
+pub fn find_gradebooks(dir: &Path) -> Result<Vec<(String, PathBuf)>, String> {
+    let entries = fs::read_dir(dir).map_err(|e| format!("Could not read {}: {}", dir.display(), e))?;
+    let mut gradebooks: Vec<(String, PathBuf)> = Vec::new();
+    let mut legacy: Vec<(String, PathBuf)> = Vec::new();
+    for path in entries.flatten().map(|x| x.path()) {
+        let file_name = path.file_name().map_or(String::new(), |x| x.to_string_lossy().to_string());
+        if let Some(name) = file_name.strip_suffix(&format!(".{}", GRADEBOOK_EXTENSION)) {
+            gradebooks.push((name.to_string(), path.clone()));
+        } else if let Some(name) = file_name.strip_suffix(&format!(".{}", LEGACY_EXTENSION)) {
+            legacy.push((name.to_string(), path.clone()));
+        }
+    }
+    legacy.retain(|x| !gradebooks.iter().any(|g| g.0 == x.0));
+    gradebooks.extend(legacy);
+    gradebooks.sort();
+    return Ok(gradebooks);
+}
+
+/// Order pokemon are stored in a legacy csv.
+/// Base forms in dex order, then alternate forms sorted by (dex_no, form)
+pub fn legacy_order(list: &[Pokemon]) -> Vec<usize> {
+    let base = (0..list.len()).filter(|i| list[*i].is_base_form());
+    let forms = (0..list.len()).filter(|i| !list[*i].is_base_form());
+    return base.chain(forms).collect();
+}
+
+pub fn now() -> u64 {
+    return SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |x| x.as_secs());
+}
@@ -0,0 +1,82 @@
+use crate::pokemon::*;
+use serde::{Deserialize, Serialize};
+
+// Oldest entries are dropped past this
+pub const JOURNAL_LIMIT: usize = 1000;
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct GradeChange {
+    #[serde(flatten)]
+    pub id: PokemonId,
+    pub grade: Option<i32>,
+    pub source: GradeSource,
+    pub previous: Option<i32>,
+    pub previous_source: GradeSource,
+}
+
+/// Grades changed by a single action, e.g. one keypress or one autofill
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct JournalEntry {
+    // Unix timestamp (seconds)
+    pub time: u64,
+    pub source: GradeSource,
+    pub changes: Vec<GradeChange>,
+}
+
+/// Undo/redo history of grade changes, saved with the gradebook
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct Journal {
+    pub entries: Vec<JournalEntry>,
+    // Entries before position are applied, the rest were undone and can be redone
+    pub position: usize,
+}
+
+impl Journal {
+    pub fn new() -> Journal {
+        return Journal::default();
+    }
+
+    /// Add entry after the current position, dropping undone entries
+    pub fn record(&mut self, entry: JournalEntry) {
+        self.entries.truncate(self.position);
+        self.entries.push(entry);
+        if self.entries.len() > JOURNAL_LIMIT {
+            self.entries.drain(..self.entries.len() - JOURNAL_LIMIT);
+        }
+        self.position = self.entries.len();
+    }
+
+    /// Restore grades from before the last applied entry. List must be sorted
+    pub fn undo(&mut self, list: &mut [Pokemon]) -> Option<&JournalEntry> {
+        if self.position == 0 {
+            return None;
+        }
+        self.position -= 1;
+        let entry = &self.entries[self.position];
+        for change in entry.changes.iter().rev() {
+            if let Some(i) = find_pokemon(list, &change.id) {
+                list[i].grade = change.previous;
+                list[i].grade_source = change.previous_source;
+            }
+        }
+        return Some(entry);
+    }
+
+    /// Apply the last undone entry again. List must be sorted
+    pub fn redo(&mut self, list: &mut [Pokemon]) -> Option<&JournalEntry> {
+        let entry = self.entries.get(self.position)?;
+        for change in &entry.changes {
+            if let Some(i) = find_pokemon(list, &change.id) {
+                list[i].grade = change.grade;
+                list[i].grade_source = change.source;
+            }
+        }
+        self.position += 1;
+        return Some(entry);
+    }
+
+    /// Applied entries, most recent first
+    pub fn history(&self) -> impl Iterator<Item = &JournalEntry> {
+        return self.entries[..self.position].iter().rev();
+    }
+}
@@ -0,0 +1,22 @@
+#![allow(clippy::needless_return)]
+
+pub mod data;
+pub mod pokemon;
+pub mod gradebook;
+pub mod analysis;
+pub mod autofill;
+pub mod rules;
+pub mod dex;
+pub mod dataset;
+pub mod pairwise;
+pub mod journal;
+pub mod regression;
+pub mod tierlist;
+pub mod stats;
+pub mod compare;
+pub mod consensus;
+pub mod typechart;
+pub mod evolution;
+
+#[cfg(test)]
+mod tests;
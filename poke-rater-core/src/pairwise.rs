@@ -0,0 +1,123 @@
+use crate::pokemon::*;
+use std::{collections::HashSet, hash::{Hash, Hasher, DefaultHasher}};
+use serde::{Deserialize, Serialize};
+
+pub const INITIAL_RATING: f64 = 1500.0;
+pub const K_FACTOR: f64 = 32.0;
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct Rating {
+    pub id: PokemonId,
+    pub rating: f64,
+    // Number of comparisons this pokemon was in
+    pub games: usize,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct Comparison {
+    pub winner: PokemonId,
+    pub loser: PokemonId,
+}
+
+/// Head-to-head rating mode.
+/// Each pick updates an Elo rating, which is mapped onto the gradebook's grades afterwards
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct PairwiseSession {
+    // Sorted by id
+    pub ratings: Vec<Rating>,
+    pub comparisons: Vec<Comparison>,
+}
+
+impl PairwiseSession {
+    pub fn new() -> PairwiseSession {
+        return PairwiseSession::default();
+    }
+
+    pub fn rating(&self, id: &PokemonId) -> f64 {
+        return self.find(id).map_or(INITIAL_RATING, |i| self.ratings[i].rating);
+    }
+
+    pub fn games(&self, id: &PokemonId) -> usize {
+        return self.find(id).map_or(0, |i| self.ratings[i].games);
+    }
+
+    fn find(&self, id: &PokemonId) -> Option<usize> {
+        return self.ratings.binary_search_by(|x| x.id.cmp(id)).ok();
+    }
+
+    fn entry(&mut self, id: &PokemonId) -> &mut Rating {
+        let i = match self.ratings.binary_search_by(|x| x.id.cmp(id)) {
+            Ok(i) => i,
+            Err(i) => {
+                self.ratings.insert(i, Rating { id: id.clone(), rating: INITIAL_RATING, games: 0 });
+                i
+            }
+        };
+        return &mut self.ratings[i];
+    }
+
+    /// Record that winner was preferred over loser
+    pub fn record(&mut self, winner: &PokemonId, loser: &PokemonId) {
+        let (w, l) = (self.rating(winner), self.rating(loser));
+        // Expected score of winner
+        let expected = 1.0 / (1.0 + 10f64.powf((l - w) / 400.0));
+        let change = K_FACTOR * (1.0 - expected);
+
+        let entry = self.entry(winner);
+        entry.rating += change;
+        entry.games += 1;
+        let entry = self.entry(loser);
+        entry.rating -= change;
+        entry.games += 1;
+
+        self.comparisons.push(Comparison { winner: winner.clone(), loser: loser.clone() });
+    }
+
+    /// Choose the next pair to show.
+    /// First is the pokemon with fewest comparisons, second is the closest rated opponent
+    /// (outcome is least predictable) that it has not met yet, preferring less compared pokemon
+    pub fn next_pair(&self, list: &[Pokemon]) -> Option<(PokemonId, PokemonId)> {
+        if list.len() < 2 {
+            return None;
+        }
+        let round = self.comparisons.len();
+        let first = list.iter()
+            .min_by_key(|x| (self.games(&x.id()), shuffle(&x.id(), round)))?
+            .id();
+
+        let met: HashSet<&PokemonId> = self.comparisons.iter()
+            .filter_map(|x| if x.winner == first { Some(&x.loser) } else if x.loser == first { Some(&x.winner) } else { None })
+            .collect();
+        let rating = self.rating(&first);
+        let score = |id: &PokemonId| (
+            met.contains(id),
+            ((self.rating(id) - rating).abs() / 10.0) as u64 + self.games(id) as u64,
+            shuffle(id, round),
+        );
+
+        let second = list.iter()
+            .map(|x| x.id())
+            .filter(|x| *x != first)
+            .min_by_key(score)?;
+        return Some((first, second));
+    }
+
+    /// Map ratings onto 1..=num_grades by rank, in equal sized groups.
+    /// Only pokemon that have been compared are graded
+    pub fn to_grades(&self, num_grades: i32) -> Vec<(PokemonId, i32)> {
+        let mut rated: Vec<&Rating> = self.ratings.iter().filter(|x| x.games > 0).collect();
+        rated.sort_by(|x, y| x.rating.total_cmp(&y.rating));
+
+        let count = rated.len();
+        return rated.iter().enumerate()
+            .map(|(rank, x)| (x.id.clone(), (rank * num_grades as usize / count) as i32 + 1))
+            .collect();
+    }
+}
+
+// Stable pseudo-random tie breaker, so equal candidates are not always shown in dex order
+fn shuffle(id: &PokemonId, round: usize) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    (id, round).hash(&mut hasher);
+    return hasher.finish();
+}
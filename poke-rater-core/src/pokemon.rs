@@ -0,0 +1,115 @@
+use crate::data::*;
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+
+/// Identifies a single gradeable entry: a species plus an optional form.
+/// Ordered by dex_no, with the base form (form = None) before its alternates.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PokemonId {
+    pub dex_no: usize,
+    pub form: Option<String>,
+}
+
+impl PokemonId {
+    /// File in src/assets/pics, e.g. 38.jpg or 38-alola.jpg
+    pub fn picture_file(&self) -> String {
+        return match &self.form {
+            Some(form) => format!("{}-{}.jpg", self.dex_no, form),
+            None => format!("{}.jpg", self.dex_no)
+        };
+    }
+}
+
+/// Where a grade came from
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GradeSource {
+    #[default]
+    Manual,
+    Autofill,
+    Pairwise,
+    // Legacy .csv gradebook, which did not record sources
+    Import,
+}
+
+impl GradeSource {
+    /// Set by hand, as far as we know
+    pub fn is_manual(&self) -> bool {
+        return matches!(self, GradeSource::Manual | GradeSource::Import);
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Pokemon {
+    pub grade: Option<i32>,
+    // Meaningless while grade is None
+    #[serde(default)]
+    pub grade_source: GradeSource,
+    pub name: String,
+    pub dex_no: usize,
+    // Regional/alternate form key, e.g. "alola", "galar", "hisui", "paldea", "mega-x", "gmax"
+    // None for the base form
+    #[serde(default)]
+    pub form: Option<String>,
+    pub color: PColors,
+    pub gen_no: usize,
+    pub typing: Vec<PTypes>,
+    pub stats: Vec<(StatNames, i32)>,
+    pub matchups: HashMap<i32, Vec<PTypes>>,
+    pub manga_count: usize,
+    pub anime_count: usize,
+    #[serde(default)]
+    pub categories: Vec<Category>,
+    // Evolution data is not in slides.json, see evolution::assign_evolutions
+    // dex_no of the family's Basic pokemon
+    #[serde(default)]
+    pub family: usize,
+    #[serde(default)]
+    pub stage: EvolutionStage,
+    #[serde(default)]
+    pub fully_evolved: bool,
+    // Evolutions in the family's longest line: 0 if it does not evolve, 2 for Bulbasaur to Venusaur
+    #[serde(default)]
+    pub evolutions: usize,
+}
+
+impl Pokemon {
+    // fn new()
+    pub fn id(&self) -> PokemonId {
+        return PokemonId { dex_no: self.dex_no, form: self.form.clone() };
+    }
+    pub fn is_base_form(&self) -> bool {
+        return self.form.is_none();
+    }
+    pub fn is_typing(&self, typing: &PTypes) -> bool {
+        return self.typing.contains(typing);
+    }
+    pub fn is_gen(&self, gen: &usize) -> bool {
+        return &self.gen_no == gen;
+    }
+    pub fn is_category(&self, category: &Category) -> bool {
+        return self.categories.contains(category);
+    }
+}
+/// Index of pokemon in list. List must be sorted
+pub fn find_pokemon(list: &[Pokemon], id: &PokemonId) -> Option<usize> {
+    return list.binary_search_by(|x| x.id().cmp(id)).ok();
+}
+
+impl PartialEq for Pokemon {
+    fn eq(&self, other: &Self) -> bool {
+        return self.dex_no == other.dex_no && self.form == other.form;
+    }
+}
+impl Eq for Pokemon {}
+impl PartialOrd for Pokemon {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+impl Ord for Pokemon {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        return self.dex_no.cmp(&other.dex_no)
+            .then_with(|| self.form.cmp(&other.form));
+    }
+}
+
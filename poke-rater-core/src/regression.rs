@@ -0,0 +1,277 @@
+use crate::data::*;
+use crate::pokemon::*;
+use std::{fmt, iter::zip};
+use serde::Serialize;
+use strum::IntoEnumIterator;
+
+// Penalty on standardised coefficients. Keeps overlapping features (e.g. stats and their total) stable
+pub const RIDGE_LAMBDA: f64 = 10.0;
+// Fewer rated pokemon than this cannot say much about what drives grades
+pub const MIN_RATED: usize = 20;
+// Pokemon listed each way in SurprisingGrades
+pub const SURPRISE_COUNT: usize = 10;
+
+/// Pokemon attribute used to explain grades
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(tag = "kind", content = "value")]
+pub enum Feature {
+    Typing(PTypes),
+    Gen(usize),
+    Color(PColors),
+    Stat(StatNames),
+    StatTotal,
+    Anime,
+    Manga,
+    DualType,
+}
+
+impl Feature {
+    pub fn all() -> Vec<Feature> {
+        let mut features: Vec<Feature> = PTypes::iter().map(Feature::Typing).collect();
+        features.extend((1..=GEN_COUNT).map(Feature::Gen));
+        features.extend(PColors::iter().map(Feature::Color));
+        features.extend(StatNames::iter().map(Feature::Stat));
+        features.extend([Feature::StatTotal, Feature::Anime, Feature::Manga, Feature::DualType]);
+        return features;
+    }
+
+    /// Yes/no features are 0 or 1
+    pub fn is_binary(&self) -> bool {
+        return matches!(self, Feature::Typing(_) | Feature::Gen(_) | Feature::Color(_) | Feature::DualType);
+    }
+
+    pub fn value(&self, pokemon: &Pokemon) -> f64 {
+        let flag = |x: bool| if x { 1.0 } else { 0.0 };
+        return match self {
+            Feature::Typing(typing) => flag(pokemon.is_typing(typing)),
+            Feature::Gen(gen_no) => flag(pokemon.is_gen(gen_no)),
+            Feature::Color(color) => flag(pokemon.color == *color),
+            Feature::Stat(stat) => pokemon.stats.iter().find(|x| x.0 == *stat).map_or(0.0, |x| x.1 as f64),
+            Feature::StatTotal => pokemon.stats.iter().map(|x| x.1 as f64).sum(),
+            Feature::Anime => pokemon.anime_count as f64,
+            Feature::Manga => pokemon.manga_count as f64,
+            Feature::DualType => flag(pokemon.typing.len() == 2),
+        };
+    }
+}
+
+impl fmt::Display for Feature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            Feature::Typing(typing) => write!(f, "{:?} type", typing),
+            Feature::Gen(gen_no) => write!(f, "Generation {}", gen_no),
+            Feature::Color(color) => write!(f, "{:?} color", color),
+            Feature::Stat(stat) => write!(f, "{:?}", stat),
+            Feature::StatTotal => write!(f, "Base stat total"),
+            Feature::Anime => write!(f, "Anime appearances"),
+            Feature::Manga => write!(f, "Manga appearances"),
+            Feature::DualType => write!(f, "Dual type"),
+        };
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct Factor {
+    pub feature: Feature,
+    // Change in grade from having a yes/no feature, or from one standard deviation more of a numeric one,
+    // with all other features held equal
+    pub effect: f64,
+}
+
+/// What drives a user's grades, controlling for overlap between features
+/// (e.g. Gen 4 having many Steel types)
+#[derive(Serialize, Debug, Clone)]
+pub struct FeatureImportance {
+    pub count: usize,
+    // Share of grade variance explained by the model
+    pub r_squared: f64,
+    // Most positive first
+    pub factors: Vec<Factor>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct Surprise {
+    pub id: PokemonId,
+    pub name: String,
+    pub grade: i32,
+    // Grade the rest of the gradebook suggests, same scale as grade
+    pub predicted: f64,
+    // grade - predicted
+    pub residual: f64,
+}
+
+/// Pokemon graded furthest from what the user's other grades predict.
+/// Also catches mis-keyed grades
+#[derive(Serialize, Debug, Clone)]
+pub struct SurprisingGrades {
+    // Largest positive residual first
+    pub higher: Vec<Surprise>,
+    // Largest negative residual first
+    pub lower: Vec<Surprise>,
+}
+
+/// Ridge regression on standardised features
+#[derive(Debug, Clone)]
+pub struct RidgeModel {
+    pub features: Vec<Feature>,
+    pub means: Vec<f64>,
+    // 0 for features that are the same for every pokemon, those are ignored
+    pub std_devs: Vec<f64>,
+    pub intercept: f64,
+    // Per standardised feature
+    pub coefficients: Vec<f64>,
+    // Number of pokemon fitted and (XᵀX + λI)⁻¹, for leave-one-out predictions
+    count: usize,
+    inverse: Vec<Vec<f64>>,
+}
+
+impl RidgeModel {
+    /// Fit grades (any scale) of the given pokemon
+    pub fn fit(pokemon: &[(&Pokemon, f64)], features: Vec<Feature>, lambda: f64) -> RidgeModel {
+        let n = pokemon.len() as f64;
+        let columns: Vec<Vec<f64>> = features.iter()
+            .map(|f| pokemon.iter().map(|x| f.value(x.0)).collect())
+            .collect();
+        let means: Vec<f64> = columns.iter().map(|c| c.iter().sum::<f64>() / n).collect();
+        let std_devs: Vec<f64> = columns.iter().zip(&means)
+            .map(|(c, m)| (c.iter().map(|x| (x - m).powi(2)).sum::<f64>() / n).sqrt())
+            .collect();
+        let x: Vec<Vec<f64>> = (0..pokemon.len())
+            .map(|i| (0..features.len()).map(|j| standardise(columns[j][i], means[j], std_devs[j])).collect())
+            .collect();
+        let intercept = pokemon.iter().map(|x| x.1).sum::<f64>() / n;
+
+        // (XᵀX + λI) β = Xᵀ(y - mean)
+        let p = features.len();
+        let mut a = vec![vec![0.0; p]; p];
+        let mut b = vec![0.0; p];
+        for (row, (_, y)) in x.iter().zip(pokemon) {
+            for j in 0..p {
+                b[j] += row[j] * (y - intercept);
+                for k in 0..p {
+                    a[j][k] += row[j] * row[k];
+                }
+            }
+        }
+        for (j, row) in a.iter_mut().enumerate() {
+            row[j] += lambda;
+        }
+        let inverse = invert(a);
+        let coefficients = inverse.iter().map(|row| row.iter().zip(&b).map(|(x, y)| x * y).sum()).collect();
+        return RidgeModel { features, means, std_devs, intercept, coefficients, count: pokemon.len(), inverse };
+    }
+
+    fn standardised(&self, pokemon: &Pokemon) -> Vec<f64> {
+        return self.features.iter().enumerate()
+            .map(|(j, f)| standardise(f.value(pokemon), self.means[j], self.std_devs[j]))
+            .collect();
+    }
+
+    pub fn predict(&self, pokemon: &Pokemon) -> f64 {
+        return self.intercept + zip(&self.coefficients, self.standardised(pokemon)).map(|(b, x)| b * x).sum::<f64>();
+    }
+
+    /// Prediction for a fitted pokemon as if it had been left out of the fit
+    pub fn predict_left_out(&self, pokemon: &Pokemon, grade: f64) -> f64 {
+        // Leave-one-out residual is residual / (1 - leverage), no refit needed
+        let x = self.standardised(pokemon);
+        let leverage = 1.0 / self.count as f64 + self.inverse.iter().zip(&x)
+            .map(|(row, xj)| xj * zip(row, &x).map(|(a, xk)| a * xk).sum::<f64>())
+            .sum::<f64>();
+        let residual = (grade - self.predict(pokemon)) / (1.0 - leverage);
+        return grade - residual;
+    }
+
+    /// Effect of each feature in grade units, see Factor
+    pub fn factors(&self) -> Vec<Factor> {
+        let mut factors: Vec<Factor> = self.features.iter().enumerate()
+            .filter(|(j, _)| self.std_devs[*j] > 0.0)
+            .map(|(j, f)| Factor {
+                feature: *f,
+                effect: if f.is_binary() { self.coefficients[j] / self.std_devs[j] } else { self.coefficients[j] },
+            })
+            .collect();
+        factors.sort_by(|x, y| y.effect.total_cmp(&x.effect));
+        return factors;
+    }
+}
+
+/// Fit rated pokemon (grades 1..=num_grades). None if too few are rated
+pub fn feature_importance(list: &[Pokemon], num_grades: i32) -> Option<FeatureImportance> {
+    let rated = rated(list, num_grades);
+    if rated.len() < MIN_RATED {
+        return None;
+    }
+    let model = RidgeModel::fit(&rated, Feature::all(), RIDGE_LAMBDA);
+
+    let mean = model.intercept;
+    let total: f64 = rated.iter().map(|x| (x.1 - mean).powi(2)).sum();
+    let residual: f64 = rated.iter().map(|x| (x.1 - model.predict(x.0)).powi(2)).sum();
+    return Some(FeatureImportance {
+        count: rated.len(),
+        r_squared: if total > 0.0 { 1.0 - residual / total } else { 0.0 },
+        factors: model.factors(),
+    });
+}
+
+/// Pokemon whose grade is furthest from the grade predicted by all other rated pokemon.
+/// None if too few are rated
+pub fn surprising_grades(list: &[Pokemon], num_grades: i32) -> Option<SurprisingGrades> {
+    let rated = rated(list, num_grades);
+    if rated.len() < MIN_RATED {
+        return None;
+    }
+    let model = RidgeModel::fit(&rated, Feature::all(), RIDGE_LAMBDA);
+
+    let mut surprises: Vec<Surprise> = rated.iter()
+        .map(|(pokemon, grade)| {
+            let predicted = model.predict_left_out(pokemon, *grade);
+            Surprise {
+                id: pokemon.id(),
+                name: pokemon.name.clone(),
+                grade: *grade as i32 + 1,
+                predicted: predicted + 1.0,
+                residual: grade - predicted,
+            }
+        })
+        .collect();
+    surprises.sort_by(|x, y| y.residual.total_cmp(&x.residual));
+
+    let higher = surprises.iter().take(SURPRISE_COUNT).filter(|x| x.residual > 0.0).cloned().collect();
+    let lower = surprises.iter().rev().take(SURPRISE_COUNT).filter(|x| x.residual < 0.0).cloned().collect();
+    return Some(SurprisingGrades { higher, lower });
+}
+
+// Pokemon with grades that fit 1..=num_grades, paired with their 0-indexed grade
+fn rated(list: &[Pokemon], num_grades: i32) -> Vec<(&Pokemon, f64)> {
+    return list.iter()
+        .filter_map(|x| x.grade.filter(|g| *g >= 1 && *g <= num_grades).map(|g| (x, (g - 1) as f64)))
+        .collect();
+}
+
+fn standardise(value: f64, mean: f64, std_dev: f64) -> f64 {
+    return if std_dev > 0.0 { (value - mean) / std_dev } else { 0.0 };
+}
+
+// Gauss-Jordan elimination with partial pivoting. a must be non-singular (ridge guarantees this)
+fn invert(mut a: Vec<Vec<f64>>) -> Vec<Vec<f64>> {
+    let n = a.len();
+    let mut inverse: Vec<Vec<f64>> = (0..n).map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect()).collect();
+    for col in 0..n {
+        let pivot = (col..n).max_by(|x, y| a[*x][col].abs().total_cmp(&a[*y][col].abs())).unwrap();
+        a.swap(col, pivot);
+        inverse.swap(col, pivot);
+
+        let scale = a[col][col];
+        a[col].iter_mut().for_each(|x| *x /= scale);
+        inverse[col].iter_mut().for_each(|x| *x /= scale);
+        for row in (0..n).filter(|x| *x != col) {
+            let factor = a[row][col];
+            for k in 0..n {
+                a[row][k] -= factor * a[col][k];
+                inverse[row][k] -= factor * inverse[col][k];
+            }
+        }
+    }
+    return inverse;
+}
@@ -0,0 +1,294 @@
+use crate::data::*;
+use crate::pokemon::*;
+use std::fmt;
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compare { Lt, Le, Eq, Ne, Ge, Gt }
+
+impl Compare {
+    pub fn test(&self, a: i64, b: i64) -> bool {
+        return match self {
+            Compare::Lt => a < b,
+            Compare::Le => a <= b,
+            Compare::Eq => a == b,
+            Compare::Ne => a != b,
+            Compare::Ge => a >= b,
+            Compare::Gt => a > b,
+        };
+    }
+    fn symbol(&self) -> &'static str {
+        return match self {
+            Compare::Lt => "<",
+            Compare::Le => "<=",
+            Compare::Eq => "=",
+            Compare::Ne => "!=",
+            Compare::Ge => ">=",
+            Compare::Gt => ">",
+        };
+    }
+}
+
+/// Condition a pokemon has to fulfill for an autofill rule to apply.
+/// Text syntax: `gen:1 and (type:Fire or type:Dragon) and not color:Red`
+///
+/// Predicates: type:<type>, gen:<n>, color:<color>, category:<category> (e.g. category:Legendary),
+/// name:<text> (case-insensitive substring, quote for spaces),
+/// <stat> <op> <n> for hp/attack/defense/spatk/spdef/speed, manga <op> <n> and anime <op> <n>
+/// where <op> is one of < <= = != >= >. `all` matches every pokemon
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind")]
+pub enum Rule {
+    // Empty And matches everything
+    And { rules: Vec<Rule> },
+    Or { rules: Vec<Rule> },
+    Not { rule: Box<Rule> },
+    Type { typing: PTypes },
+    Gen { gen_no: usize },
+    Color { color: PColors },
+    Category { category: Category },
+    Stat { stat: StatNames, cmp: Compare, value: i32 },
+    Manga { cmp: Compare, value: usize },
+    Anime { cmp: Compare, value: usize },
+    Name { text: String },
+}
+
+impl Rule {
+    pub fn all() -> Rule {
+        return Rule::And { rules: Vec::new() };
+    }
+
+    pub fn parse(text: &str) -> Result<Rule, String> {
+        let mut parser = Parser { tokens: tokenize(text)?, pos: 0 };
+        if parser.tokens.is_empty() {
+            return Err("Rule is empty".to_string());
+        }
+        let rule = parser.or()?;
+        if let Some(token) = parser.peek() {
+            return Err(format!("Unexpected '{}'", token));
+        }
+        return Ok(rule);
+    }
+
+    pub fn matches(&self, pokemon: &Pokemon) -> bool {
+        return match self {
+            Rule::And { rules } => rules.iter().all(|x| x.matches(pokemon)),
+            Rule::Or { rules } => rules.iter().any(|x| x.matches(pokemon)),
+            Rule::Not { rule } => !rule.matches(pokemon),
+            Rule::Type { typing } => pokemon.is_typing(typing),
+            Rule::Gen { gen_no } => pokemon.is_gen(gen_no),
+            Rule::Color { color } => pokemon.color == *color,
+            Rule::Category { category } => pokemon.is_category(category),
+            Rule::Stat { stat, cmp, value } => pokemon.stats.iter()
+                .find(|x| x.0 == *stat)
+                .is_some_and(|x| cmp.test(x.1 as i64, *value as i64)),
+            Rule::Manga { cmp, value } => cmp.test(pokemon.manga_count as i64, *value as i64),
+            Rule::Anime { cmp, value } => cmp.test(pokemon.anime_count as i64, *value as i64),
+            Rule::Name { text } => pokemon.name.to_lowercase().contains(&text.to_lowercase()),
+        };
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Parenthesise children that bind looser than their parent
+        let child = |rule: &Rule, parent_is_or: bool| match rule {
+            Rule::Or { rules } if rules.len() > 1 && !parent_is_or => format!("({})", rule),
+            _ => rule.to_string()
+        };
+        return match self {
+            Rule::And { rules } if rules.is_empty() => write!(f, "all"),
+            Rule::And { rules } => write!(f, "{}", rules.iter().map(|x| child(x, false)).collect::<Vec<_>>().join(" and ")),
+            Rule::Or { rules } if rules.is_empty() => write!(f, "not all"),
+            Rule::Or { rules } => write!(f, "{}", rules.iter().map(|x| child(x, true)).collect::<Vec<_>>().join(" or ")),
+            Rule::Not { rule } => match **rule {
+                Rule::And { ref rules } | Rule::Or { ref rules } if rules.len() > 1 => write!(f, "not ({})", rule),
+                _ => write!(f, "not {}", rule)
+            },
+            Rule::Type { typing } => write!(f, "type:{:?}", typing),
+            Rule::Gen { gen_no } => write!(f, "gen:{}", gen_no),
+            Rule::Color { color } => write!(f, "color:{:?}", color),
+            Rule::Category { category } => write!(f, "category:{:?}", category),
+            Rule::Stat { stat, cmp, value } => write!(f, "{}{}{}", format!("{:?}", stat).to_lowercase(), cmp.symbol(), value),
+            Rule::Manga { cmp, value } => write!(f, "manga{}{}", cmp.symbol(), value),
+            Rule::Anime { cmp, value } => write!(f, "anime{}{}", cmp.symbol(), value),
+            Rule::Name { text } if text.chars().all(|c| c.is_alphanumeric() || c == '-') => write!(f, "name:{}", text),
+            // Backslash escapes, see tokenize
+            Rule::Name { text } => write!(f, "name:\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\"")),
+        };
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    Colon,
+    Op(Compare),
+    Word(String),
+    // Quoted, never a keyword
+    Text(String),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            Token::Open => write!(f, "("),
+            Token::Close => write!(f, ")"),
+            Token::Colon => write!(f, ":"),
+            Token::Op(cmp) => write!(f, "{}", cmp.symbol()),
+            Token::Word(word) => write!(f, "{}", word),
+            Token::Text(text) => write!(f, "\"{}\"", text),
+        };
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            ':' => Token::Colon,
+            '=' => Token::Op(Compare::Eq),
+            '<' | '>' | '!' => {
+                let or_equal = chars.next_if_eq(&'=').is_some();
+                match (c, or_equal) {
+                    ('<', false) => Token::Op(Compare::Lt),
+                    ('<', true) => Token::Op(Compare::Le),
+                    ('>', false) => Token::Op(Compare::Gt),
+                    ('>', true) => Token::Op(Compare::Ge),
+                    ('!', true) => Token::Op(Compare::Ne),
+                    _ => return Err("Expected '=' after '!'".to_string())
+                }
+            },
+            '"' => {
+                let mut quoted = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c) => quoted.push(c),
+                            None => return Err("Missing closing '\"'".to_string())
+                        },
+                        Some(c) => quoted.push(c),
+                        None => return Err("Missing closing '\"'".to_string())
+                    }
+                }
+                Token::Text(quoted)
+            },
+            _ => {
+                let mut word = c.to_string();
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace() && !"():=<>!\"".contains(*c)) {
+                    word.push(c);
+                }
+                Token::Word(word)
+            }
+        };
+        tokens.push(token);
+    }
+    return Ok(tokens);
+}
+
+// Recursive descent, precedence from loosest to tightest: or, and, not
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        return self.tokens.get(self.pos);
+    }
+
+    fn next(&mut self) -> Result<Token, String> {
+        let token = self.tokens.get(self.pos).cloned().ok_or("Rule ends unexpectedly")?;
+        self.pos += 1;
+        return Ok(token);
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        return matches!(self.peek(), Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword));
+    }
+
+    fn or(&mut self) -> Result<Rule, String> {
+        let mut rules = vec![self.and()?];
+        while self.is_keyword("or") {
+            self.pos += 1;
+            rules.push(self.and()?);
+        }
+        return Ok(if rules.len() == 1 { rules.pop().unwrap() } else { Rule::Or { rules } });
+    }
+
+    fn and(&mut self) -> Result<Rule, String> {
+        let mut rules = vec![self.not()?];
+        while self.is_keyword("and") {
+            self.pos += 1;
+            rules.push(self.not()?);
+        }
+        return Ok(if rules.len() == 1 { rules.pop().unwrap() } else { Rule::And { rules } });
+    }
+
+    fn not(&mut self) -> Result<Rule, String> {
+        if self.is_keyword("not") {
+            self.pos += 1;
+            return Ok(Rule::Not { rule: Box::new(self.not()?) });
+        }
+        if self.peek() == Some(&Token::Open) {
+            self.pos += 1;
+            let rule = self.or()?;
+            if self.next()? != Token::Close {
+                return Err("Missing ')'".to_string());
+            }
+            return Ok(rule);
+        }
+        return self.predicate();
+    }
+
+    fn predicate(&mut self) -> Result<Rule, String> {
+        let key = match self.next()? {
+            Token::Word(word) => word.to_lowercase(),
+            token => return Err(format!("Unexpected '{}'", token))
+        };
+        if key == "all" {
+            return Ok(Rule::all());
+        }
+        let cmp = match self.next()? {
+            Token::Colon => Compare::Eq,
+            Token::Op(cmp) => cmp,
+            token => return Err(format!("Expected ':' or comparison after '{}', found '{}'", key, token))
+        };
+        let value = match self.next()? {
+            Token::Word(value) | Token::Text(value) => value,
+            token => return Err(format!("Expected value after '{}', found '{}'", key, token))
+        };
+
+        let number = |value: &str| value.parse::<i32>().map_err(|_| format!("'{}' is not a number", value));
+        let rule = match key.as_str() {
+            "type" => Rule::Type { typing: find_variant(PTypes::iter(), &value, "type")? },
+            "gen" => Rule::Gen { gen_no: number(&value)?.max(0) as usize },
+            "color" => Rule::Color { color: find_variant(PColors::iter(), &value, "color")? },
+            "category" => Rule::Category { category: find_variant(Category::iter(), &value, "category")? },
+            "name" => Rule::Name { text: value },
+            "manga" => Rule::Manga { cmp, value: number(&value)?.max(0) as usize },
+            "anime" => Rule::Anime { cmp, value: number(&value)?.max(0) as usize },
+            _ => match StatNames::iter().find(|x| format!("{:?}", x).eq_ignore_ascii_case(&key)) {
+                Some(stat) => Rule::Stat { stat, cmp, value: number(&value)? },
+                None => return Err(format!("Unknown predicate '{}'", key))
+            }
+        };
+        // Only numeric predicates can be compared
+        if cmp != Compare::Eq && !matches!(rule, Rule::Stat { .. } | Rule::Manga { .. } | Rule::Anime { .. }) {
+            return Err(format!("'{}' only supports ':'", key));
+        }
+        return Ok(rule);
+    }
+}
+
+fn find_variant<T: fmt::Debug>(mut variants: impl Iterator<Item = T>, value: &str, what: &str) -> Result<T, String> {
+    return variants.find(|x| format!("{:?}", x).eq_ignore_ascii_case(value))
+        .ok_or(format!("Unknown {} '{}'", what, value));
+}
@@ -0,0 +1,56 @@
+//! Small statistics helpers shared by the gradebook comparisons.
+//! Results that need more values than given are NaN
+use std::cmp::Ordering;
+
+pub fn mean(values: &[f64]) -> f64 {
+    return values.iter().sum::<f64>() / values.len() as f64;
+}
+
+/// Rank (1-based) of each value, tied values share the mean of their ranks
+pub fn ranks(values: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|x, y| values[*x].total_cmp(&values[*y]));
+
+    let mut ranks = vec![0.0; values.len()];
+    let mut start = 0;
+    while start < order.len() {
+        let end = (start..order.len()).find(|i| values[order[*i]] != values[order[start]]).unwrap_or(order.len());
+        // Positions start..end are tied
+        let rank = (start + end + 1) as f64 / 2.0;
+        for i in &order[start..end] {
+            ranks[*i] = rank;
+        }
+        start = end;
+    }
+    return ranks;
+}
+
+pub fn pearson(x: &[f64], y: &[f64]) -> f64 {
+    let (mx, my) = (mean(x), mean(y));
+    let covariance: f64 = x.iter().zip(y).map(|(a, b)| (a - mx) * (b - my)).sum();
+    let vx: f64 = x.iter().map(|a| (a - mx).powi(2)).sum();
+    let vy: f64 = y.iter().map(|b| (b - my).powi(2)).sum();
+    return covariance / (vx * vy).sqrt();
+}
+
+/// Rank correlation, -1 (opposite order) to 1 (same order)
+pub fn spearman(x: &[f64], y: &[f64]) -> f64 {
+    return pearson(&ranks(x), &ranks(y));
+}
+
+/// Kendall's tau-b, which accounts for ties (common with few grades)
+pub fn kendall(x: &[f64], y: &[f64]) -> f64 {
+    let (mut concordant, mut discordant, mut tied_x, mut tied_y) = (0.0_f64, 0.0, 0.0, 0.0);
+    for i in 0..x.len() {
+        for j in (i + 1)..x.len() {
+            match (x[i].total_cmp(&x[j]), y[i].total_cmp(&y[j])) {
+                (Ordering::Equal, Ordering::Equal) => (),
+                (Ordering::Equal, _) => tied_x += 1.0,
+                (_, Ordering::Equal) => tied_y += 1.0,
+                (dx, dy) if dx == dy => concordant += 1.0,
+                _ => discordant += 1.0
+            };
+        }
+    }
+    return (concordant - discordant) / ((concordant + discordant + tied_x) * (concordant + discordant + tied_y)).sqrt();
+}
@@ -0,0 +1,971 @@
+use crate::{data::*, pokemon::*, gradebook::*, analysis::*, dex::*, dataset::*, tierlist::*, rules::*, regression::*, compare::*, consensus::*, journal::*, typechart::*, evolution::*, autofill::is_rule_match};
+use std::{io::Read, fs::{File, self}, iter::zip, path::Path};
+use strum::IntoEnumIterator;
+
+const PATH_ROOT: &str = "test-csvs";
+const JSON_PATH: &str = "test-csvs/slides.json";
+const PICS_PATH: &str = "../src/assets/pics";
+
+
+fn load_pokemon_json() -> Vec<Pokemon> {
+    let file = fs::read_to_string(JSON_PATH).expect("Could not open slides.json");
+    //File::open(JSON_PATH).expect("Could not open slides.json");
+    
+    let mut slides: Vec<Vec<Pokemon>> = serde_json::from_str(&file).expect("Could not open slides.json");
+    assign_evolutions(&mut slides);
+    
+    let mut list: Vec<Pokemon> = Vec::new();
+    for slide in slides {
+        for pokemon in slide {
+            list.push(pokemon);
+        }
+    }
+    list.sort();
+    return list;
+}
+fn get_path_name(file_name: &str) -> String {
+    return format!("{}/{}.csv", PATH_ROOT, file_name);
+}
+fn load_csv(file_name: &str) -> Vec<Pokemon>{
+    let path = get_path_name(file_name);
+    // let mut list = List(load_pokemon_json().into());
+    let mut list = load_pokemon_json();
+    let mut file: String = String::new();
+    
+    File::open(path).expect("").read_to_string(&mut file).expect("");
+
+    let csv = file.split("\n").last().expect("Could not split newlines").to_string();
+
+    let grades = csv.split(",");
+    
+    for grade in grades.enumerate() {
+        if grade.0 == POKEMON_COUNT {
+            break;
+        }
+        list[grade.0].grade = grade.1.parse::<i32>().ok().filter(|g| *g > 0);
+    }
+    return list;
+}
+
+#[test]
+fn test_generation_avg() {
+    let list = load_csv("generation");
+    let analysis = run_analysis(&list, 9);
+    assert_eq!(analysis.gen_average[0], 0.0);
+    assert_eq!(analysis.gen_average[1], 1.0);
+    assert_eq!(analysis.gen_average[2], 2.0);
+    assert_eq!(analysis.gen_average[3], 3.0);
+    assert_eq!(analysis.gen_average[4], 4.0);
+    assert_eq!(analysis.gen_average[5], 5.0);
+    assert_eq!(analysis.gen_average[6], 6.0);
+    assert_eq!(analysis.gen_average[7], 7.0);
+    assert_eq!(analysis.gen_average[8], 8.0);
+}
+
+#[test]
+fn test_typing_avg() {
+    let list = load_csv("typing");
+    let analysis = run_analysis(&list, 18);
+    for avg in analysis.typing_average {
+        match avg.0 {
+            PTypes::Normal => 
+                assert_eq!(avg.1, 0.5846153846153846),
+            PTypes::Grass => 
+                assert_eq!(avg.1, 2.7049180327868854),
+            PTypes::Water => 
+                assert_eq!(avg.1, 2.9285714285714284),
+            PTypes::Fire => 
+                assert_eq!(avg.1, 4.5375),
+            PTypes::Electric => 
+                assert_eq!(avg.1, 4.705882352941177),
+            PTypes::Fighting => 
+                assert_eq!(avg.1, 5.819444444444445),
+            PTypes::Flying => 
+                assert_eq!(avg.1, 6.302752293577981),
+            PTypes::Poison =>   
+                assert_eq!(avg.1, 7.0),
+            PTypes::Ground => 
+                assert_eq!(avg.1, 7.8133333333333335),
+            PTypes::Psychic => 
+                assert_eq!(avg.1, 8.535353535353535),
+            PTypes::Rock => 
+                assert_eq!(avg.1, 9.63013698630137),
+            PTypes::Ice => 
+                assert_eq!(avg.1, 9.9375),
+            PTypes::Bug => 
+                assert_eq!(avg.1, 11.467391304347826),
+            PTypes::Dragon =>
+                assert_eq!(avg.1, 10.507692307692308),
+            PTypes::Ghost => 
+                assert_eq!(avg.1, 11.370967741935484),
+            PTypes::Dark => 
+                assert_eq!(avg.1, 11.666666666666666),
+            PTypes::Steel => 
+                assert_eq!(avg.1, 12.698412698412698),
+            PTypes::Fairy => 
+                assert_eq!(avg.1, 11.777777777777779)
+        }
+    }
+}
+
+#[test]
+fn test_numtypes_avg() {
+    let list = load_csv("numtypes");
+    let analysis = run_analysis(&list, 2);
+    assert_eq!(analysis.dual_type_average, 1.0);
+    assert_eq!(analysis.single_type_average, 0.0);
+}
+
+#[test]
+fn test_color_avg() {
+    let list = load_csv("color");
+    let analysis = run_analysis(&list, 18);
+    for color in analysis.color_average {
+        match color.0 {
+            PColors::White => 
+                assert_eq!(color.1, 8.0),
+            PColors::Black => 
+                assert_eq!(color.1, 0.0),
+            PColors::Gray => 
+                assert_eq!(color.1, 3.0),
+            PColors::Blue => 
+                assert_eq!(color.1, 1.0),
+            PColors::Red => 
+                assert_eq!(color.1, 7.0),
+            PColors::Green => 
+                assert_eq!(color.1, 4.0),
+            PColors::Pink => 
+                assert_eq!(color.1, 5.0),
+            PColors::Purple => 
+                assert_eq!(color.1, 6.0),
+            PColors::Brown => 
+                assert_eq!(color.1, 2.0),
+            PColors::Yellow => 
+                assert_eq!(color.1, 9.0),
+        }
+    }
+}
+
+#[test]
+fn test_best_worst() {
+    let list = load_csv("best_worst");
+    let analysis = run_analysis(&list, 3);
+    let perfect_scores = ["Bulbasaur", "Chickorita", "Suicune","Treecko","Rayquaza", "Turtwig","Giratina"];
+    let worst_scores = ["Charizard", "Dragonite", "Typhlosion","Tyranitar", "Blaziken", "Metagross","Salamence", "Infernape", "Garchomp"];
+
+    for perfect in analysis.perfect_scores {
+        assert!(perfect_scores.contains(&perfect.as_str()));
+    } 
+    for worst in analysis.worst_scores {
+        assert!(worst_scores.contains(&worst.as_str()));
+    }
+}
+
+#[test]
+fn test_appearances() {
+    // Each gen inc(0 -> 9)
+    let list = load_csv("generation");
+    let analysis = run_analysis(&list, 9);
+    // avg appearances per gen
+    let anime_count = [36.76158940397351, 22.07, 15.451851851851853, 10.299065420560748, 10.833333333333334, 7.833333333333333, 4.829545454545454, 2.8541666666666665, 0.26666666666666666];
+    
+    for avg in zip(analysis.anime_average, anime_count) {
+        assert_eq!(avg.0, avg.1);
+    }
+}
+#[test]
+fn test_matchups() {
+    let list = load_csv("matchups");
+    let analysis = run_analysis(&list, 4);
+
+    println!("{:?}", analysis.matchup_data[2]);
+    // All pure ghost types are 2
+    assert_eq!(analysis.matchup_data[2][&PTypes::Normal], 0.0);
+    assert_eq!(analysis.matchup_data[2][&PTypes::Ghost], 200.0);
+    
+    // All pure normal types are 3
+    assert_eq!(analysis.matchup_data[3][&PTypes::Fighting], 200.0);
+    assert_eq!(analysis.matchup_data[3][&PTypes::Ghost], 0.0);
+
+    // All Dragon +(flying|ground|grass) are 1
+    assert_eq!(analysis.matchup_data[1][&PTypes::Dragon], 200.0);
+    assert_eq!(analysis.matchup_data[1][&PTypes::Ice], 400.0);
+}
+#[test]
+fn test_stats() {
+    let list = load_csv("stats");
+    let analysis = run_analysis(&list,3);
+    
+    // att > 150 -> 3
+    println!("{}", analysis.stats_data[2][&StatNames::Attack]);
+    assert!(analysis.stats_data[2][&StatNames::Attack] >= 150.0);
+    // def > 150 -> 2
+    assert!(analysis.stats_data[1][&StatNames::Defense] >= 150.0);
+
+    assert!(analysis.stats_data[0][&StatNames::Attack] < 150.0);
+    
+}
+#[test]
+fn test_forms() {
+    let mut list = load_pokemon_json();
+    let mut alolan = list[37].clone();
+    alolan.form = Some(String::from("alola"));
+    alolan.typing = vec![PTypes::Ice, PTypes::Fairy];
+    list.push(alolan);
+    list.sort();
+
+    // Base form is sorted before its alternates
+    let base = find_pokemon(&list, &PokemonId { dex_no: 38, form: None }).unwrap();
+    let form = find_pokemon(&list, &PokemonId { dex_no: 38, form: Some(String::from("alola")) }).unwrap();
+    assert_eq!(form, base + 1);
+    assert!(list[form].is_typing(&PTypes::Ice));
+
+    // Forms are written after every base species
+    let order = legacy_order(&list);
+    assert_eq!(order.len(), POKEMON_COUNT + 1);
+    assert_eq!(order[37], base);
+    assert_eq!(order[POKEMON_COUNT], form);
+}
+
+#[test]
+fn test_group_forms() {
+    let base = |dex_no| PokemonId { dex_no, form: None };
+    let alt = |dex_no, form: &str| PokemonId { dex_no, form: Some(String::from(form)) };
+    let mut slides = vec![
+        vec![base(37), base(38)],
+        vec![base(52), base(53)],
+        vec![alt(38, "alola"), alt(52, "galar")],
+    ];
+    group_forms(&mut slides);
+    assert_eq!(slides, vec![
+        vec![base(37), base(38), alt(38, "alola")],
+        vec![base(52), alt(52, "galar"), base(53)],
+    ]);
+}
+
+#[test]
+fn test_gradebook_json() {
+    let mut list = load_csv("generation");
+    let mut gradebook = Gradebook::new(vec![String::from("Bad"), String::from("Good")]);
+    gradebook.update(&list, Some(PokemonId { dex_no: 25, form: None }));
+
+    let loaded = Gradebook::from_json(&gradebook.to_json()).unwrap();
+    assert_eq!(loaded.version, GRADEBOOK_VERSION);
+    assert_eq!(loaded.max_grade, 2);
+    assert_eq!(loaded.grades, gradebook.grades);
+    assert_eq!(loaded.cursor, gradebook.cursor);
+
+    // Grades follow the id, not the position in the list
+    list.remove(0);
+    let missing = loaded.apply(&mut list);
+    assert_eq!(missing, vec![PokemonId { dex_no: 1, form: None }]);
+    assert_eq!(list[0].dex_no, 2);
+    assert_eq!(list[0].grade, Some(1));
+    assert_eq!(list[POKEMON_COUNT - 2].grade, Some(9));
+
+    let newer = gradebook.to_json().replace(
+        &format!("\"version\": {}", GRADEBOOK_VERSION), 
+        &format!("\"version\": {}", GRADEBOOK_VERSION + 1));
+    assert!(Gradebook::from_json(&newer).is_err());
+}
+
+#[test]
+fn test_legacy_csv() {
+    let list = load_pokemon_json();
+    let gradebook = Gradebook::from_legacy_csv("F,D,C,B,A,S\n6,5,|4,3", &list);
+    assert_eq!(gradebook.grade_labels, vec!["F", "D", "C", "B", "A", "S"]);
+    assert_eq!(gradebook.max_grade, 6);
+    assert_eq!(gradebook.cursor, Some(PokemonId { dex_no: 3, form: None }));
+    assert_eq!(gradebook.grades.len(), 4);
+    assert_eq!(gradebook.grades[2], GradeEntry { id: PokemonId { dex_no: 3, form: None }, grade: 4, source: GradeSource::Import });
+
+    // No header: default numeric labels
+    let gradebook = Gradebook::from_legacy_csv("1,2,3", &list);
+    assert_eq!(gradebook.grade_labels, vec!["1", "2", "3", "4", "5"]);
+
+    // 0, empty and garbage are unrated
+    let gradebook = Gradebook::from_legacy_csv("0,,x,|2", &list);
+    assert_eq!(gradebook.grades, vec![GradeEntry { id: PokemonId { dex_no: 4, form: None }, grade: 2, source: GradeSource::Import }]);
+    assert_eq!(gradebook.cursor, Some(PokemonId { dex_no: 4, form: None }));
+}
+
+#[test]
+fn test_coverage() {
+    let mut list = load_csv("generation");
+    // Unrated and out of range grades are skipped, not counted as the lowest grade
+    list[0].grade = None;
+    list[1].grade = Some(0);
+    list[2].grade = Some(10);
+    let analysis = run_analysis(&list, 9);
+
+    assert_eq!(analysis.coverage.rated, POKEMON_COUNT - 3);
+    assert_eq!(analysis.coverage.unrated, 3);
+    assert_eq!(analysis.coverage.gen[0].1, 3);
+    assert_eq!(analysis.coverage.gen[1], (100, 0));
+    let grass = analysis.coverage.typing.iter().find(|x| x.0 == PTypes::Grass).unwrap();
+    assert_eq!(grass.2, 3);
+    assert_eq!(analysis.gen_average[0], 0.0);
+    assert!(!analysis.worst_scores.contains(&String::from("Bulbasaur")));
+}
+
+#[test]
+fn test_embedded_dataset() {
+    let dex = Dex::embedded().unwrap();
+    assert_eq!(dex.list.len(), POKEMON_COUNT);
+    assert_eq!(dex.list[POKEMON_COUNT - 1].dex_no, POKEMON_COUNT);
+}
+
+#[test]
+fn test_dataset_errors() {
+    let file = fs::read_to_string(JSON_PATH).unwrap();
+    let mut slides: Vec<Vec<Pokemon>> = serde_json::from_str(&file).unwrap();
+    let id = |dex_no| PokemonId { dex_no, form: None };
+
+    // Bulbasaur twice, Ivysaur removed
+    slides[0][1] = slides[0][0].clone();
+    slides[0][2].gen_no = GEN_COUNT + 1;
+    slides[1][0].stats.pop();
+    slides[1][1].matchups.get_mut(&200).unwrap().push(PTypes::Fire);
+    slides[1][2].typing = vec![PTypes::Fire, PTypes::Fire];
+
+    let errors = validate_slides(&slides);
+    assert!(errors.contains(&DatasetError::DuplicateId { id: id(1) }));
+    assert!(errors.contains(&DatasetError::MissingDexNo { dex_no: 2 }));
+    assert!(errors.contains(&DatasetError::InvalidGeneration { id: id(3), gen_no: GEN_COUNT + 1 }));
+    assert!(errors.iter().any(|x| matches!(x, DatasetError::MissingStat { id, .. } if id.dex_no == 4)));
+    assert!(errors.contains(&DatasetError::DuplicateMatchup { id: id(5), typing: PTypes::Fire }));
+    assert!(errors.contains(&DatasetError::InvalidTyping { id: id(6), typing: vec![PTypes::Fire, PTypes::Fire] }));
+    assert_eq!(errors.len(), 6);
+
+    let report = load_slides("[[{]]").unwrap_err();
+    assert!(matches!(report.errors[0], DatasetError::Parse { .. }));
+}
+
+#[test]
+fn test_pairwise() {
+    let mut dex = Dex::embedded().unwrap();
+    dex.new_gradebook((1..=3).map(|x| x.to_string()).collect());
+    let id = |dex_no| PokemonId { dex_no, form: None };
+
+    // Higher dex_no always wins
+    for _ in 0..5 {
+        for a in 1..=6 {
+            for b in (a + 1)..=6 {
+                dex.record_preference(&id(b), &id(a)).unwrap();
+            }
+        }
+    }
+    let session = &dex.gradebook.as_ref().unwrap().pairwise;
+    for dex_no in 1..6 {
+        assert!(session.rating(&id(dex_no)) < session.rating(&id(dex_no + 1)));
+    }
+
+    // Next pair includes a pokemon that has never been compared
+    let (first, second) = dex.next_pair().unwrap();
+    assert_eq!(session.games(&first), 0);
+    assert_ne!(first, second);
+
+    assert_eq!(dex.apply_pairwise().unwrap(), 6);
+    let grades: Vec<Option<i32>> = (1..=7).map(|x| dex.get(&id(x)).unwrap().grade).collect();
+    assert_eq!(grades, vec![Some(1), Some(1), Some(2), Some(2), Some(3), Some(3), None]);
+
+    assert!(dex.record_preference(&id(1), &id(1)).is_err());
+    assert!(dex.record_preference(&id(1), &id(POKEMON_COUNT + 1)).is_err());
+}
+
+#[test]
+fn test_tier_list() {
+    let mut dex = Dex::embedded().unwrap();
+    let id = |dex_no| PokemonId { dex_no, form: None };
+    let labels: Vec<String> = ["C", "B", "I could see it on my team"].map(String::from).to_vec();
+    dex.set_grade(&id(1), Some(3));
+    dex.set_grade(&id(4), Some(3));
+    dex.set_grade(&id(7), Some(1));
+
+    // Best tier first, custom order before dex order
+    let tiers = build_tiers(&dex.list, &labels, Some(&[id(4)]));
+    assert_eq!(tiers.len(), 3);
+    assert_eq!(tiers[0].label, labels[2]);
+    assert_eq!(tiers[0].pokemon, vec![id(4), id(1)]);
+    assert!(tiers[1].pokemon.is_empty());
+    assert_eq!(tiers[2].pokemon, vec![id(7)]);
+
+    let style = TierListStyle { sprite_size: 16, columns: 1, label_width: 160 };
+    let pics = Path::new(PICS_PATH);
+    let svg = render_svg(&tiers, pics, &style);
+    assert!(svg.starts_with("<svg"));
+    // Jpgs are embedded, missing pictures are left as a placeholder
+    assert_eq!(svg.matches("data:image/jpeg;base64,/9j/").count(), 3);
+    assert_eq!(render_svg(&tiers, Path::new("missing"), &style).matches("<image").count(), 0);
+
+    let png = image::load_from_memory(&render_png(&tiers, pics, &style).unwrap()).unwrap();
+    // 2 sprites in top tier, empty tier keeps one line
+    assert_eq!((png.width(), png.height()), (160 + 16, 16 * 4));
+}
+
+#[test]
+fn test_rules() {
+    let dex = Dex::embedded().unwrap();
+    let get = |dex_no| dex.get(&PokemonId { dex_no, form: None }).unwrap();
+    let (charmander, charizard, pikachu, dragonite) = (get(4), get(6), get(25), get(149));
+
+    let rule = Rule::parse("gen:1 and (type:Fire or type:Dragon) and not color:Red").unwrap();
+    assert!(!rule.matches(charmander));
+    assert!(rule.matches(dragonite));
+    assert!(!rule.matches(pikachu));
+    assert_eq!(rule.to_string(), "gen:1 and (type:Fire or type:Dragon) and not color:Red");
+    assert_eq!(Rule::parse(&rule.to_string()).unwrap(), rule);
+
+    // and binds tighter than or, keywords and values are case-insensitive
+    let rule = Rule::parse("TYPE:fire AND Type:Flying or name:PIKA").unwrap();
+    assert!(rule.matches(charizard) && rule.matches(pikachu) && !rule.matches(charmander));
+    assert!(Rule::parse("attack>=84 and spatk>100 and manga!=0").unwrap().matches(charizard));
+    assert!(!Rule::parse("hp < 78 or anime=43").unwrap().matches(charizard));
+    assert!(Rule::parse("name:\"Charmel\"").unwrap().matches(get(5)));
+    assert!(Rule::parse("all").unwrap().matches(pikachu));
+
+    // Quotes and backslashes in names survive Display and parse
+    for text in ["Mr. Mime", "say \"cheese\"", "back\\slash", "\""] {
+        let rule = Rule::Name { text: text.to_string() };
+        assert_eq!(Rule::parse(&rule.to_string()).unwrap(), rule, "{}", rule);
+    }
+    assert!(Rule::parse("name:\"oops\\\"").is_err());
+
+    for bad in ["", "gen:1 and", "(type:Fire", "type:Wood", "color>Red", "size:3", "hp>tall", "gen:1 gen:2"] {
+        assert!(Rule::parse(bad).is_err(), "{}", bad);
+    }
+
+    // Frontend sends text, old rule files use type_rule/gen_rule fields
+    let rules: Vec<AutofillRules> = serde_json::from_str(r#"[
+        { "rule": "type:Fire and not type:Flying", "grade": 1 },
+        { "rule": { "kind": "Gen", "gen_no": 1 }, "grade": 2 },
+        { "type_rule1": "Fire", "type_rule2": null, "gen_rule1": 1, "gen_rule2": null, "grade": 3 }
+    ]"#).unwrap();
+    assert!(rules[0].rule.matches(charmander) && !rules[0].rule.matches(charizard));
+    assert!(rules[1].rule.matches(pikachu));
+    assert!(rules[2].rule.matches(charizard) && !rules[2].rule.matches(pikachu));
+    assert!(serde_json::from_str::<Vec<AutofillRules>>(r#"[{ "rule": "type:", "grade": 1 }]"#).is_err());
+}
+
+#[test]
+fn test_autofill() {
+    let mut dex = Dex::embedded().unwrap();
+    let id = |dex_no| PokemonId { dex_no, form: None };
+    let rule = |text: &str, grade, priority, mode| AutofillRules { rule: Rule::parse(text).unwrap(), grade, priority, mode };
+    dex.set_grade(&id(4), Some(1));
+    dex.set_grade_from(&id(5), Some(1), GradeSource::Pairwise);
+
+    let rules = vec![
+        rule("type:Fire", 3, 1, AutofillMode::KeepManual),
+        rule("gen:1", 2, 0, AutofillMode::UnratedOnly),
+        // Loses to the first rule despite coming later
+        rule("type:Fire and type:Flying", 5, 0, AutofillMode::Overwrite),
+    ];
+    let report = dex.autofill(&rules);
+    let get = |dex_no| dex.get(&id(dex_no)).unwrap();
+    // Manual grade kept, pairwise grade replaced
+    assert_eq!((get(4).grade, get(4).grade_source), (Some(1), GradeSource::Manual));
+    assert_eq!((get(5).grade, get(5).grade_source), (Some(3), GradeSource::Autofill));
+    assert_eq!(get(6).grade, Some(3));
+    assert_eq!(get(25).grade, Some(2));
+    assert_eq!(get(152).grade, None);
+
+    let gen1_fire = dex.list.iter().filter(|x| x.gen_no == 1 && x.is_typing(&PTypes::Fire)).count();
+    let gen1 = dex.list.iter().filter(|x| x.gen_no == 1).count();
+    assert_eq!(report.changed_per_rule[1], gen1 - gen1_fire);
+    assert_eq!(report.changed_per_rule[2], 0);
+    assert_eq!(report.changed, report.changed_per_rule.iter().sum::<usize>());
+
+    // Grades already given by the winning rule are not counted again
+    assert_eq!(dex.autofill(&rules).changed, 0);
+
+    // Sources survive saving
+    let mut gradebook = Gradebook::new((1..=5).map(|x| x.to_string()).collect());
+    gradebook.update(&dex.list, None);
+    let mut list = dex.list.clone();
+    Gradebook::from_json(&gradebook.to_json()).unwrap().apply(&mut list);
+    assert_eq!(list[3].grade_source, GradeSource::Manual);
+    assert_eq!(list[4].grade_source, GradeSource::Autofill);
+}
+
+#[test]
+fn test_autofill_undo() {
+    let mut dex = Dex::embedded().unwrap();
+    let id = |dex_no| PokemonId { dex_no, form: None };
+    let rules = vec![AutofillRules { rule: Rule::parse("type:Fire").unwrap(), grade: 4, priority: 0, mode: AutofillMode::Overwrite }];
+    dex.set_grade(&id(4), Some(1));
+    assert!(dex.undo_autofill().is_err());
+
+    // Preview does not change anything
+    let changes = dex.preview_autofill(&rules);
+    assert_eq!(changes.iter().find(|x| x.id == id(4)).unwrap().old, Some(1));
+    assert_eq!(dex.get(&id(4)).unwrap().grade, Some(1));
+    assert_eq!(dex.autofill(&rules).changed, changes.len());
+
+    // Grades edited after autofill are kept
+    dex.set_grade(&id(5), Some(2));
+    assert_eq!(dex.undo_autofill().unwrap(), changes.len() - 1);
+    assert_eq!((dex.get(&id(4)).unwrap().grade, dex.get(&id(4)).unwrap().grade_source), (Some(1), GradeSource::Manual));
+    assert_eq!(dex.get(&id(5)).unwrap().grade, Some(2));
+    assert_eq!(dex.get(&id(6)).unwrap().grade, None);
+    assert!(dex.undo_autofill().is_err());
+}
+
+#[test]
+fn test_journal() {
+    let mut dex = Dex::embedded().unwrap();
+    let id = |dex_no| PokemonId { dex_no, form: None };
+    let labels: Vec<String> = (1..=5).map(|x| x.to_string()).collect();
+    dex.new_gradebook(labels.clone());
+    assert!(dex.undo().is_err());
+
+    dex.set_grade(&id(1), Some(2));
+    dex.set_grade(&id(1), Some(3));
+    // Unchanged grades are not recorded
+    dex.set_grade(&id(1), Some(3));
+    dex.autofill(&[AutofillRules { rule: Rule::parse("gen:1").unwrap(), grade: 4, priority: 0, mode: AutofillMode::UnratedOnly }]);
+    assert_eq!(dex.history().len(), 3);
+    assert_eq!(dex.history()[0].source, GradeSource::Autofill);
+
+    // Whole autofill is undone at once
+    let entry = dex.undo().unwrap();
+    assert!(entry.changes.len() > 100);
+    assert_eq!(dex.get(&id(2)).unwrap().grade, None);
+    assert_eq!(dex.undo().unwrap().changes[0].previous, Some(2));
+    assert_eq!(dex.get(&id(1)).unwrap().grade, Some(2));
+    dex.redo().unwrap();
+    assert_eq!(dex.get(&id(1)).unwrap().grade, Some(3));
+
+    // History survives saving and loading, a new edit drops what can be redone
+    let path = std::env::temp_dir().join("poke-rater-test-journal.gradebook.json");
+    dex.save_gradebook(&path, labels, None).unwrap();
+    let mut dex = Dex::embedded().unwrap();
+    dex.load_gradebook(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(dex.get(&id(1)).unwrap().grade, Some(3));
+    dex.redo().unwrap();
+    assert_eq!(dex.get(&id(2)).unwrap().grade, Some(4));
+    dex.undo().unwrap();
+    dex.set_grade(&id(2), Some(1));
+    assert!(dex.redo().is_err());
+    dex.undo().unwrap();
+    dex.undo().unwrap();
+    dex.undo().unwrap();
+    assert_eq!(dex.get(&id(1)).unwrap().grade, None);
+    assert!(dex.undo().is_err());
+}
+
+#[test]
+fn test_journal_undo_saved() {
+    // The app saves after every undo/redo, a reload must not bring back the undone grade
+    let mut dex = Dex::embedded().unwrap();
+    let id = PokemonId { dex_no: 7, form: None };
+    let labels: Vec<String> = (1..=5).map(|x| x.to_string()).collect();
+    dex.new_gradebook(labels.clone());
+    dex.set_grade(&id, Some(2));
+    dex.set_grade(&id, Some(5));
+    dex.undo().unwrap();
+
+    let path = std::env::temp_dir().join("poke-rater-test-journal-undo.gradebook.json");
+    dex.save_gradebook(&path, labels, None).unwrap();
+    let mut dex = Dex::embedded().unwrap();
+    dex.load_gradebook(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(dex.get(&id).unwrap().grade, Some(2));
+    assert_eq!((dex.journal.entries.len(), dex.journal.position), (2, 1));
+    dex.redo().unwrap();
+    assert_eq!(dex.get(&id).unwrap().grade, Some(5));
+}
+
+#[test]
+fn test_distribution() {
+    // Grades 0, 1, 1, 3
+    let distribution = Distribution::from_histogram(vec![1, 2, 0, 1]);
+    assert_eq!(distribution.count, 4);
+    assert_eq!(distribution.mean, 1.25);
+    assert_eq!(distribution.median, 1.0);
+    assert!((distribution.std_dev - 1.258306).abs() < 1e-6);
+    // t = 3.182 for 3 degrees of freedom
+    assert!((distribution.ci_high - (1.25 + 3.182 * 1.258306 / 2.0)).abs() < 1e-5);
+    assert_eq!(Distribution::from_histogram(vec![1, 0, 1]).median, 1.0);
+    assert!(Distribution::from_histogram(vec![0, 1]).std_dev.is_nan());
+    assert!(Distribution::from_histogram(vec![0, 0]).mean.is_nan());
+
+    let list = load_csv("typing");
+    let analysis = run_analysis(&list, 18);
+    assert_eq!(analysis.overall_distribution.count, POKEMON_COUNT);
+    for (typing, distribution) in &analysis.typing_distribution {
+        let average = analysis.typing_average.iter().find(|x| x.0 == *typing).unwrap().1;
+        assert!((distribution.mean - average).abs() < 1e-9);
+        assert_eq!(distribution.histogram.len(), 18);
+    }
+    let gen_counts: Vec<usize> = analysis.gen_distribution.iter().map(|x| x.count).collect();
+    assert_eq!(gen_counts, analysis.coverage.gen.iter().map(|x| x.0).collect::<Vec<usize>>());
+    assert_eq!(analysis.single_type_distribution.count + analysis.dual_type_distribution.count, POKEMON_COUNT);
+}
+
+#[test]
+fn test_feature_importance() {
+    // Grade is the generation
+    let importance = run_analysis(&load_csv("generation"), 9).feature_importance.unwrap();
+    assert!(importance.r_squared > 0.99);
+    assert_eq!(importance.factors.first().unwrap().feature, Feature::Gen(9));
+    assert_eq!(importance.factors.last().unwrap().feature, Feature::Gen(1));
+    let effect = |feature| importance.factors.iter().find(|x| x.feature == feature).unwrap().effect;
+    assert!((effect(Feature::Gen(9)) - effect(Feature::Gen(1)) - 8.0).abs() < 0.5);
+    assert!(effect(Feature::StatTotal).abs() < 0.1);
+
+    // Grade only depends on typing
+    let importance = run_analysis(&load_csv("typing"), 18).feature_importance.unwrap();
+    assert_eq!(importance.factors.last().unwrap().feature, Feature::Typing(PTypes::Normal));
+    assert!(importance.factors[..5].iter().all(|x| matches!(x.feature, Feature::Typing(_))));
+
+    let mut list = load_pokemon_json();
+    list[0].grade = Some(1);
+    assert!(run_analysis(&list, 5).feature_importance.is_none());
+}
+
+#[test]
+fn test_surprising_grades() {
+    // Grade is the generation, apart from a mis-keyed Bulbasaur
+    let mut list = load_csv("generation");
+    list[0].grade = Some(9);
+    let surprises = run_analysis(&list, 9).surprising_grades.unwrap();
+    let top = &surprises.higher[0];
+    assert_eq!(top.name, "Bulbasaur");
+    assert_eq!(top.grade, 9);
+    // Prediction leaves Bulbasaur out, so it is not pulled towards 9
+    assert!(top.predicted < 2.0, "{}", top.predicted);
+    assert!(surprises.higher.iter().skip(1).all(|x| x.residual < 1.0));
+    assert!(surprises.lower.iter().all(|x| x.residual < 0.0 && x.residual > -1.0));
+    assert!(surprises.higher.len() <= SURPRISE_COUNT);
+}
+
+#[test]
+fn test_compare() {
+    // Same ranking on a 9 and a 17 grade scale, apart from Bulbasaur
+    let list = load_csv("generation");
+    let labels = |n: i32| (1..=n).map(|x| x.to_string()).collect::<Vec<String>>();
+    let mut a = Gradebook::new(labels(9));
+    a.update(&list, None);
+    let mut b = Gradebook::new(labels(17));
+    b.grades = a.grades.iter().map(|x| GradeEntry { grade: 2 * x.grade - 1, ..x.clone() }).collect();
+    b.grades[0].grade = 17;
+    b.grade_labels[16] = String::from("S");
+    // Unrated in a
+    a.grades.remove(1);
+
+    let comparison = compare_gradebooks(&list, &a, &b);
+    assert_eq!(comparison.count, b.grades.len() - 1);
+    assert_eq!((comparison.only_a, comparison.only_b), (0, 1));
+    assert!(comparison.overall.spearman > 0.99 && comparison.kendall > 0.99);
+    assert!(comparison.overall.mean_bias < 0.0);
+
+    let top = &comparison.disagreements[0];
+    assert_eq!(top.name, "Bulbasaur");
+    assert_eq!((top.grade_a, top.grade_b), (1, 17));
+    assert_eq!((top.label_a.as_str(), top.label_b.as_str()), ("1", "S"));
+    assert_eq!(top.difference, -1.0);
+    assert!(comparison.disagreements[1].difference.abs() < 1e-9);
+    // Gen 2 has no disagreements, so identical normalised grades
+    assert_eq!(comparison.gen_agreement[1].mean_difference, 0.0);
+    assert!(comparison.gen_agreement[0].mean_difference > 0.0);
+}
+
+#[test]
+fn test_consensus() {
+    // Three raters agree the grade is the generation (on different scales), except the last on Bulbasaur
+    let list = load_csv("generation");
+    let labels = |n: i32| (1..=n).map(|x| x.to_string()).collect::<Vec<String>>();
+    let mut a = Gradebook::new(labels(9));
+    a.update(&list, None);
+    let mut b = Gradebook::new(labels(17));
+    b.grades = a.grades.iter().map(|x| GradeEntry { grade: 2 * x.grade - 1, ..x.clone() }).collect();
+    let mut c = a.clone();
+    c.grades[0].grade = 9;
+    let gradebooks = vec![(String::from("a"), a), (String::from("b"), b), (String::from("c"), c)];
+
+    for normalisation in [Normalisation::ZScore, Normalisation::Rank] {
+        let output = consensus(&list, &gradebooks, normalisation, &labels(9));
+        assert_eq!(output.ranking.len(), list.len());
+        assert!(output.ranking[0].id.dex_no >= 906, "{:?}", output.ranking[0]);
+        assert_eq!(output.controversial[0].name, "Bulbasaur");
+        assert_eq!(output.raters[2].contrarian[0].name, "Bulbasaur");
+        assert!(output.raters[2].contrarian[0].difference > 0.0);
+        assert!(output.raters.iter().all(|x| x.agreement > 0.9));
+
+        // Every pokemon is in one tier, tiers follow the generation split
+        assert_eq!(output.tiers.len(), 9);
+        assert_eq!(output.tiers.iter().map(|x| x.pokemon.len()).sum::<usize>(), list.len());
+        let gen9 = list.iter().filter(|x| x.is_gen(&9)).count();
+        assert!(output.tiers[0].pokemon.len().abs_diff(gen9) <= 1);
+    }
+
+    let found = find_gradebooks(Path::new(PATH_ROOT)).unwrap();
+    assert!(found.iter().any(|x| x.0 == "generation"));
+}
+
+#[test]
+fn test_normalised_scores() {
+    let mut list = load_csv("generation");
+    let analysis = run_analysis(&list, 9);
+    assert_eq!(analysis.gen_normalised[0].score, 0.0);
+    assert_eq!(analysis.gen_normalised[8].score, 1.0);
+    assert!(analysis.gen_normalised.windows(2).all(|x| x[0].percentile < x[1].percentile));
+    let gen1 = list.iter().filter(|x| x.is_gen(&1)).count() as f64;
+    assert!((analysis.gen_normalised[0].percentile - gen1 / 2.0 / list.len() as f64 * 100.0).abs() < 1e-9);
+
+    // Same grades on a 17 grade scale give the same normalised scores
+    let labels = |n: i32| (1..=n).map(|x| x.to_string()).collect::<Vec<String>>();
+    let mut gradebook = Gradebook::new(labels(9));
+    gradebook.update(&list, None);
+    gradebook.journal.record(JournalEntry { time: 0, source: GradeSource::Manual, changes: Vec::new() });
+    let rescaled = gradebook.rescale(labels(17)).unwrap();
+    assert_eq!(rescaled.max_grade, 17);
+    assert!(rescaled.journal.history().next().is_none());
+    assert!(zip(&gradebook.grades, &rescaled.grades).all(|(a, b)| b.grade == 2 * a.grade - 1));
+
+    rescaled.apply(&mut list);
+    let other = run_analysis(&list, 17);
+    assert_eq!(analysis.gen_normalised, other.gen_normalised);
+    assert_eq!(analysis.typing_normalised, other.typing_normalised);
+
+    // Fewer grades round to the nearest position
+    let small = gradebook.rescale(labels(5)).unwrap();
+    assert_eq!((small.grades[0].grade, small.grades.last().unwrap().grade), (1, 5));
+    assert_eq!(gradebook.rescale_grade(5, 5), 3);
+    assert_eq!(gradebook.rescale_grade(4, 5), 3);
+
+    // A single label would collapse every grade
+    assert!(gradebook.rescale(labels(1)).is_err());
+    assert!(gradebook.rescale(Vec::new()).is_err());
+}
+
+#[test]
+fn test_type_matrix() {
+    // Single types get 1, dual types 2
+    let mut list = load_pokemon_json();
+    for pokemon in list.iter_mut() {
+        pokemon.grade = Some(pokemon.typing.len() as i32);
+    }
+    let analysis = run_analysis(&list, 2);
+    let matrix = &analysis.type_matrix;
+    let index = |t: PTypes| matrix.types.iter().position(|x| *x == t).unwrap();
+
+    assert_eq!(matrix.counts.iter().flatten().sum::<usize>(), list.len());
+    for (i, row) in matrix.averages.iter().enumerate() {
+        for (j, average) in row.iter().enumerate() {
+            match matrix.counts[i][j] {
+                0 => assert!(average.is_nan()),
+                _ => assert_eq!(*average, if i == j { 0.0 } else { 1.0 })
+            };
+        }
+    }
+    // Order matters: Charizard is Fire/Flying
+    let charizard = list.iter().find(|x| x.name == "Charizard").unwrap();
+    assert_eq!(charizard.typing, vec![PTypes::Fire, PTypes::Flying]);
+    let fire_flying = list.iter().filter(|x| x.typing == charizard.typing).count();
+    assert_eq!(matrix.counts[index(PTypes::Fire)][index(PTypes::Flying)], fire_flying);
+
+    let primary = &analysis.primary_type_distribution.iter().find(|x| x.0 == PTypes::Flying).unwrap().1;
+    let secondary = &analysis.secondary_type_distribution.iter().find(|x| x.0 == PTypes::Flying).unwrap().1;
+    assert_eq!(primary.count, list.iter().filter(|x| x.typing[0] == PTypes::Flying).count());
+    assert!(secondary.count > primary.count);
+    assert_eq!(secondary.mean, 1.0);
+}
+
+#[test]
+fn test_cross_tabs() {
+    // Poison types from Hoenn are the favorite
+    let mut list = load_pokemon_json();
+    for pokemon in list.iter_mut() {
+        pokemon.grade = Some(if pokemon.is_gen(&3) && pokemon.is_typing(&PTypes::Poison) { 5 } else { 2 });
+    }
+    let analysis = run_analysis(&list, 5);
+    let poison = analysis.gen_typing.columns.iter().position(|x| *x == PTypes::Poison).unwrap();
+    assert_eq!(analysis.gen_typing.averages[2][poison], 4.0);
+    assert_eq!(analysis.gen_typing.averages[0][poison], 1.0);
+    let hoenn_poison = list.iter().filter(|x| x.is_gen(&3) && x.is_typing(&PTypes::Poison)).count();
+    assert_eq!(analysis.gen_typing.counts[2][poison], hoenn_poison);
+    assert_eq!(analysis.gen_color.counts.iter().flatten().sum::<usize>(), list.len());
+
+    let best = &analysis.best_combinations[0];
+    assert_eq!((best.gen_no, best.feature), (3, Feature::Typing(PTypes::Poison)));
+    assert!(analysis.best_combinations.iter().all(|x| x.count >= MIN_COMBINATION_COUNT));
+    assert!(analysis.worst_combinations.iter().all(|x| x.average < analysis.overall_distribution.mean));
+    assert!(analysis.worst_combinations.len() <= COMBINATION_COUNT);
+}
+
+#[test]
+fn test_type_chart() {
+    assert_eq!(effectiveness(PTypes::Fire, PTypes::Grass), 2.0);
+    assert_eq!(effectiveness(PTypes::Normal, PTypes::Ghost), 0.0);
+    assert_eq!(multiplier(PTypes::Rock, &[PTypes::Fire, PTypes::Flying]), 4.0);
+    assert_eq!(stab_multiplier(&[PTypes::Water, PTypes::Ground], &[PTypes::Fire, PTypes::Rock]), 4.0);
+
+    // The dataset's matchups mostly come from typing alone.
+    // The rest are abilities such as Levitate, Flash Fire, Water Absorb and Thick Fat
+    let list = load_pokemon_json();
+    let matching = list.iter()
+        .filter(|p| p.matchups.iter().all(|(m, types)| types.iter().all(|t| (multiplier(*t, &p.typing) * 100.0) as i32 == *m)))
+        .count();
+    assert!(matching as f64 > list.len() as f64 * 0.85, "{} of {}", matching, list.len());
+
+    // Normal types: weak to Fighting, immune to Ghost. Their own moves are resisted by Rock and Steel, no effect on Ghost
+    let mut list = load_pokemon_json();
+    for pokemon in list.iter_mut() {
+        let normal = pokemon.typing == vec![PTypes::Normal] && ability(pokemon).is_none();
+        pokemon.grade = Some(if normal { 2 } else { 1 });
+    }
+    let analysis = run_analysis(&list, 2);
+    let normal = &analysis.matchup_summary[1];
+    assert_eq!(normal.count, list.iter().filter(|x| x.grade == Some(2)).count());
+    assert_eq!((normal.weaknesses, normal.resistances, normal.immunities), (1.0, 0.0, 1.0));
+    assert_eq!((normal.super_effective, normal.walled_by), (0.0, 3.0));
+    assert_eq!(analysis.stab_coverage_data[1][&PTypes::Ghost], 0.0);
+    assert!(analysis.stab_coverage_data[0][&PTypes::Ghost] > 0.0);
+}
+
+#[test]
+fn test_check_matchups() {
+    let list = load_pokemon_json();
+    let charizard = &list[find_pokemon(&list, &PokemonId { dex_no: 6, form: None }).unwrap()];
+    assert_eq!(derive_matchups(charizard), charizard.matchups);
+
+    // Levitate
+    let gengar = &list[find_pokemon(&list, &PokemonId { dex_no: 94, form: None }).unwrap()];
+    assert_eq!(ability(gengar), None);
+    let haunter = &list[find_pokemon(&list, &PokemonId { dex_no: 93, form: None }).unwrap()];
+    assert_eq!(defensive_multiplier(PTypes::Ground, haunter), 0.0);
+
+    // Abilities off the dataset's scale go to the nearest bucket
+    assert_eq!(bucket(1.25), 100);
+    assert_eq!(bucket(0.125), 25);
+    assert_eq!(bucket(0.5 + 1e-12), 50);
+    assert_eq!(bucket(0.0), 0);
+    assert_eq!(bucket(8.0), 400);
+    let dewgong = &list[find_pokemon(&list, &PokemonId { dex_no: 87, form: None }).unwrap()];
+    let heliolisk = &list[find_pokemon(&list, &PokemonId { dex_no: 695, form: None }).unwrap()];
+    assert_eq!((ability(dewgong), ability(heliolisk)), (Some(Ability::ThickFat), Some(Ability::DrySkin)));
+    assert_eq!(defensive_multiplier(PTypes::Ice, dewgong), 0.125);
+    assert_eq!(defensive_multiplier(PTypes::Fire, heliolisk), 1.25);
+    assert!(check_matchups(std::slice::from_ref(dewgong)).is_empty());
+    assert!(derive_matchups(heliolisk).values().all(|x| !x.contains(&PTypes::Fire)));
+
+    // Derived matchups are valid dataset matchups
+    let mut derived = list.clone();
+    for pokemon in derived.iter_mut() {
+        pokemon.matchups = derive_matchups(pokemon);
+    }
+    assert_eq!(validate_slides(&[derived]), Vec::new());
+
+    // What's left is slides.json storing Dry Skin's 1.25x Fire as 2x
+    let mismatches = check_matchups(&list);
+    assert!(!mismatches.is_empty());
+    assert!(mismatches.iter().all(|x| ability(&list[find_pokemon(&list, &x.id).unwrap()]) == Some(Ability::DrySkin)
+        && (x.attack, x.scraped, x.computed) == (PTypes::Fire, 200, 100)), "{:?}", mismatches);
+}
+
+#[test]
+fn test_evolutions() {
+    let list = load_pokemon_json();
+    let get = |dex_no| &list[find_pokemon(&list, &PokemonId { dex_no, form: None }).unwrap()];
+    let line = |x: &Pokemon| (x.family, x.stage, x.fully_evolved, x.evolutions);
+
+    // Babies are Basic
+    assert_eq!(line(get(172)), (172, EvolutionStage::Basic, false, 2));
+    assert_eq!(line(get(25)), (172, EvolutionStage::Stage1, false, 2));
+    assert_eq!(line(get(26)), (172, EvolutionStage::Stage2, true, 2));
+    // Branches, and Eevee's evolutions from other slides
+    assert_eq!(line(get(182)), (43, EvolutionStage::Stage2, true, 2));
+    assert_eq!(line(get(44)), (43, EvolutionStage::Stage1, false, 2));
+    assert_eq!(line(get(197)), (133, EvolutionStage::Stage1, true, 1));
+    assert!(!get(133).fully_evolved);
+    // Plusle and Minun share a slide but do not evolve
+    assert_eq!(line(get(312)), (312, EvolutionStage::Basic, true, 0));
+    assert_eq!(line(get(128)), (128, EvolutionStage::Basic, true, 0));
+    // Every family starts with its Basic pokemon
+    assert!(list.iter().all(|x| get(x.family).stage == EvolutionStage::Basic));
+    assert_eq!(Dex::embedded().unwrap().get(&get(26).id()).unwrap().family, 172);
+
+    // Final evolutions graded higher than the rest of their family
+    let mut list = list.clone();
+    for pokemon in list.iter_mut() {
+        pokemon.grade = Some(if pokemon.fully_evolved { 3 } else { 1 });
+    }
+    let analysis = run_analysis(&list, 3);
+    let preference = &analysis.evolution_preference;
+    assert!(preference.families > 300);
+    assert_eq!((preference.prefer_final, preference.prefer_basic, preference.ties), (preference.families, 0, 0));
+    assert_eq!(preference.mean_difference, 2.0);
+    assert!(preference.stage_deviation[0].1 < 0.0 && preference.stage_deviation[2].1 > 0.0);
+    assert_eq!(analysis.fully_evolved_distribution.mean, 2.0);
+    assert_eq!(analysis.stage_distribution.iter().map(|x| x.1.count).sum::<usize>(), list.len());
+    // Pokemon that can evolve are in families with at least one evolution
+    assert!(analysis.evolutions_average[0] >= 1.0 && analysis.evolutions_average[1].is_nan());
+}
+
+#[test]
+fn test_family_consistency() {
+    let mut list = load_pokemon_json();
+    for pokemon in list.iter_mut() {
+        pokemon.grade = Some(match pokemon.dex_no { 4 => 6, 6 => 1, 7 => 5, 8 | 9 => 4, _ => 3 });
+    }
+    let consistency = family_consistency(&list, 6);
+
+    // Loved Charmander, hated Charizard
+    let charmander = &consistency.widest[0];
+    assert_eq!((charmander.family, charmander.name.as_str()), (4, "Charmander"));
+    assert_eq!(charmander.members, vec![(String::from("Charmander"), 6), (String::from("Charmeleon"), 3), (String::from("Charizard"), 1)]);
+    assert_eq!((charmander.spread, charmander.trend), (5.0, -5.0));
+    assert_eq!((consistency.widest[1].family, consistency.widest[1].spread), (7, 1.0));
+    assert_eq!(consistency.widest.len(), 2);
+
+    // Everyone else is uniform, largest families first
+    assert_eq!(consistency.uniform.len(), consistency.families - 2);
+    assert_eq!(consistency.uniform[0].name, "Eevee");
+    assert!(consistency.uniform.iter().all(|x| x.trend == 0.0 || x.trend.is_nan()));
+    assert!(consistency.mean_trend < 0.0);
+    assert!(consistency.stage_average[0].1 > consistency.stage_average[2].1);
+}
+
+#[test]
+fn test_categories() {
+    let list = load_pokemon_json();
+    let get = |dex_no| &list[find_pokemon(&list, &PokemonId { dex_no, form: None }).unwrap()];
+    let count = |category| list.iter().filter(|x| x.is_category(&category)).count();
+    assert_eq!((count(Category::Legendary), count(Category::Mythical), count(Category::Paradox), count(Category::UltraBeast)), (66, 22, 16, 11));
+    assert_eq!(get(150).categories, vec![Category::Legendary]);
+    assert_eq!(get(6).categories, vec![Category::Starter]);
+    assert_eq!(get(149).categories, vec![Category::PseudoLegendary]);
+    assert!(get(148).categories.is_empty());
+    assert!(get(172).is_category(&Category::Baby));
+
+    // Autofill predicate
+    let rule = Rule::parse("category:legendary or category:Mythical").unwrap();
+    assert_eq!(rule.to_string(), "category:Legendary or category:Mythical");
+    assert_eq!(list.iter().filter(|x| rule.matches(x)).count(), 88);
+    assert!(Rule::parse("category:Shiny").is_err());
+    assert!(Rule::parse("category>Legendary").is_err());
+    let rule = AutofillRules { rule: Rule::parse("category:UltraBeast").unwrap(), grade: 2, priority: 0, mode: AutofillMode::Overwrite };
+    assert!(is_rule_match(get(793), &rule) && !is_rule_match(get(1), &rule));
+
+    // Legendaries loved, starters disliked
+    let mut list = list.clone();
+    for pokemon in list.iter_mut() {
+        pokemon.grade = Some(if pokemon.is_category(&Category::Legendary) { 3 } else if pokemon.is_category(&Category::Starter) { 1 } else { 2 });
+    }
+    let analysis = run_analysis(&list, 3);
+    assert_eq!(analysis.category_average.first().unwrap(), &(Category::Legendary, 2.0));
+    assert_eq!(analysis.category_average.last().unwrap(), &(Category::Starter, 0.0));
+    assert_eq!(analysis.category_distribution.len(), Category::iter().count());
+    assert_eq!(analysis.category_distribution[0].1.count, 66);
+}
+
+#[test]
+fn test_analysis_grade_count() {
+    let dex = Dex::embedded().unwrap();
+    assert!(dex.analyze(0).is_err());
+    assert!(dex.analyze(-1).is_err());
+    assert_eq!(dex.analyze(1).unwrap().coverage.unrated, dex.list.len());
+}
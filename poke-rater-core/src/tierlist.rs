@@ -0,0 +1,298 @@
+use crate::pokemon::*;
+use std::{fs, io::Cursor, path::Path, collections::HashSet};
+use image::{imageops, imageops::FilterType, DynamicImage, ImageOutputFormat, Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
+
+// Row colors from the top tier down, repeated if there are more tiers
+const TIER_COLORS: [[u8; 3]; 10] = [
+    [255, 127, 127], [255, 191, 127], [255, 223, 127], [255, 255, 127], [191, 255, 127],
+    [127, 255, 127], [127, 255, 255], [127, 191, 255], [127, 127, 255], [255, 127, 255],
+];
+const BACKGROUND: [u8; 3] = [26, 26, 23];
+const MISSING_SPRITE: [u8; 3] = [96, 96, 96];
+
+/// One row of the tier list
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct Tier {
+    pub grade: i32,
+    pub label: String,
+    pub pokemon: Vec<PokemonId>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TierListStyle {
+    // Width and height of each sprite (px)
+    pub sprite_size: u32,
+    // Sprites per line before a tier wraps
+    pub columns: u32,
+    pub label_width: u32,
+}
+
+impl Default for TierListStyle {
+    fn default() -> TierListStyle {
+        return TierListStyle { sprite_size: 96, columns: 10, label_width: 160 };
+    }
+}
+
+/// Group graded pokemon into tiers, best grade first.
+/// Pokemon in order come first (e.g. slide order), the rest follow in dex order.
+/// Unrated pokemon are left out
+pub fn build_tiers(list: &[Pokemon], grade_labels: &[String], order: Option<&[PokemonId]>) -> Vec<Tier> {
+    let mut tiers: Vec<Tier> = (1..=grade_labels.len() as i32).rev()
+        .map(|grade| Tier { grade, label: grade_labels[grade as usize - 1].clone(), pokemon: Vec::new() })
+        .collect();
+
+    let mut ids: Vec<PokemonId> = order.map_or(Vec::new(), |x| x.to_vec());
+    let ordered: HashSet<PokemonId> = ids.iter().cloned().collect();
+    ids.extend(list.iter().map(|x| x.id()).filter(|x| !ordered.contains(x)));
+
+    for id in ids {
+        let grade = match find_pokemon(list, &id).and_then(|i| list[i].grade) {
+            Some(g) => g,
+            None => continue
+        };
+        if let Some(tier) = tiers.iter_mut().find(|x| x.grade == grade) {
+            tier.pokemon.push(id);
+        }
+    }
+    return tiers;
+}
+
+/// Height of each tier (px). Empty tiers keep one line
+fn tier_heights(tiers: &[Tier], style: &TierListStyle) -> Vec<u32> {
+    return tiers.iter()
+        .map(|x| (x.pokemon.len() as u32).div_ceil(style.columns).max(1) * style.sprite_size)
+        .collect();
+}
+
+fn tier_color(index: usize) -> [u8; 3] {
+    return TIER_COLORS[index % TIER_COLORS.len()];
+}
+
+/// Self-contained SVG, sprites are embedded as base64 jpgs
+pub fn render_svg(tiers: &[Tier], pics_dir: &Path, style: &TierListStyle) -> String {
+    let heights = tier_heights(tiers, style);
+    let width = style.label_width + style.columns * style.sprite_size;
+    let height: u32 = heights.iter().sum();
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n\
+         <rect width=\"{w}\" height=\"{h}\" fill=\"{}\"/>\n", rgb(BACKGROUND), w = width, h = height);
+
+    let mut y = 0;
+    for (i, tier) in tiers.iter().enumerate() {
+        svg += &format!("<rect x=\"0\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\" stroke=\"#000\"/>\n",
+            y, style.label_width, heights[i], rgb(tier_color(i)));
+
+        let lines = wrap(&tier.label, 12);
+        let line_height = 24;
+        let top = y + heights[i] / 2 + line_height / 3 - (lines.len() as u32 - 1) * line_height / 2;
+        svg += &format!("<text x=\"{}\" y=\"{}\" font-family=\"sans-serif\" font-size=\"20\" text-anchor=\"middle\">",
+            style.label_width / 2, top);
+        for (l, line) in lines.iter().enumerate() {
+            svg += &format!("<tspan x=\"{}\" dy=\"{}\">{}</tspan>",
+                style.label_width / 2, if l == 0 { 0 } else { line_height }, escape(line));
+        }
+        svg += "</text>\n";
+
+        for (n, id) in tier.pokemon.iter().enumerate() {
+            let (x, sprite_y) = sprite_position(n as u32, y, style);
+            match fs::read(pics_dir.join(id.picture_file())) {
+                Ok(bytes) => svg += &format!(
+                    "<image x=\"{}\" y=\"{}\" width=\"{s}\" height=\"{s}\" href=\"data:image/jpeg;base64,{}\"/>\n",
+                    x, sprite_y, base64(&bytes), s = style.sprite_size),
+                Err(_) => svg += &format!(
+                    "<rect x=\"{}\" y=\"{}\" width=\"{s}\" height=\"{s}\" fill=\"{}\"/>\n",
+                    x, sprite_y, rgb(MISSING_SPRITE), s = style.sprite_size),
+            };
+        }
+        y += heights[i];
+    }
+    svg += "</svg>\n";
+    return svg;
+}
+
+/// PNG bytes. Labels are drawn with a built in bitmap font, so no system fonts are needed
+pub fn render_png(tiers: &[Tier], pics_dir: &Path, style: &TierListStyle) -> Result<Vec<u8>, String> {
+    let heights = tier_heights(tiers, style);
+    let width = style.label_width + style.columns * style.sprite_size;
+    let height: u32 = heights.iter().sum();
+    let mut canvas = RgbaImage::from_pixel(width, height.max(1), rgba(BACKGROUND));
+
+    let mut y = 0;
+    for (i, tier) in tiers.iter().enumerate() {
+        fill(&mut canvas, 0, y, style.label_width, heights[i], rgba(tier_color(i)));
+        draw_label(&mut canvas, &tier.label, y, heights[i], style.label_width);
+
+        for (n, id) in tier.pokemon.iter().enumerate() {
+            let (x, sprite_y) = sprite_position(n as u32, y, style);
+            match image::open(pics_dir.join(id.picture_file())) {
+                Ok(sprite) => {
+                    let sprite = imageops::resize(&sprite.to_rgba8(), style.sprite_size, style.sprite_size, FilterType::Triangle);
+                    imageops::overlay(&mut canvas, &sprite, x as i64, sprite_y as i64);
+                },
+                Err(_) => fill(&mut canvas, x, sprite_y, style.sprite_size, style.sprite_size, rgba(MISSING_SPRITE))
+            };
+        }
+        y += heights[i];
+    }
+
+    let mut bytes: Vec<u8> = Vec::new();
+    DynamicImage::ImageRgba8(canvas)
+        .write_to(&mut Cursor::new(&mut bytes), ImageOutputFormat::Png)
+        .map_err(|e| format!("Could not encode png: {}", e))?;
+    return Ok(bytes);
+}
+
+/* Drawing helpers */
+
+fn sprite_position(n: u32, tier_y: u32, style: &TierListStyle) -> (u32, u32) {
+    let x = style.label_width + (n % style.columns) * style.sprite_size;
+    let y = tier_y + (n / style.columns) * style.sprite_size;
+    return (x, y);
+}
+
+fn rgb(color: [u8; 3]) -> String {
+    return format!("#{:02x}{:02x}{:02x}", color[0], color[1], color[2]);
+}
+
+fn rgba(color: [u8; 3]) -> Rgba<u8> {
+    return Rgba([color[0], color[1], color[2], 255]);
+}
+
+fn fill(canvas: &mut RgbaImage, x: u32, y: u32, width: u32, height: u32, color: Rgba<u8>) {
+    for py in y..(y + height).min(canvas.height()) {
+        for px in x..(x + width).min(canvas.width()) {
+            canvas.put_pixel(px, py, color);
+        }
+    }
+}
+
+fn escape(text: &str) -> String {
+    return text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;");
+}
+
+/// Split text into lines of at most max_chars, breaking between words where possible
+fn wrap(text: &str, max_chars: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > max_chars {
+            lines.push(line);
+            line = String::new();
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line += word;
+    }
+    lines.push(line);
+    return lines;
+}
+
+/// Centered label. Large text when it fits on one line, otherwise smaller wrapped text
+fn draw_label(canvas: &mut RgbaImage, label: &str, tier_y: u32, tier_height: u32, label_width: u32) {
+    const GLYPH_WIDTH: u32 = 6;
+    const GLYPH_HEIGHT: u32 = 8;
+    let padding = 8;
+
+    let mut scale = 4;
+    let mut lines = vec![label.to_string()];
+    if label.chars().count() as u32 * GLYPH_WIDTH * scale > label_width - padding {
+        scale = 2;
+        lines = wrap(label, ((label_width - padding) / (GLYPH_WIDTH * scale)) as usize);
+    }
+
+    let text_height = lines.len() as u32 * GLYPH_HEIGHT * scale;
+    let mut y = tier_y + tier_height.saturating_sub(text_height) / 2;
+    for line in lines {
+        let line_width = line.chars().count() as u32 * GLYPH_WIDTH * scale;
+        let mut x = label_width.saturating_sub(line_width) / 2;
+        for c in line.chars() {
+            for (row, bits) in glyph(c).iter().enumerate() {
+                for (col, bit) in bits.chars().enumerate() {
+                    if bit == '#' {
+                        fill(canvas, x + col as u32 * scale, y + row as u32 * scale, scale, scale, Rgba([0, 0, 0, 255]));
+                    }
+                }
+            }
+            x += GLYPH_WIDTH * scale;
+        }
+        y += GLYPH_HEIGHT * scale;
+    }
+}
+
+/// 5x7 bitmap. Lowercase is drawn as uppercase, unknown characters as a box
+fn glyph(c: char) -> [&'static str; 7] {
+    return match c.to_ascii_uppercase() {
+        ' ' => [".....", ".....", ".....", ".....", ".....", ".....", "....."],
+        '0' => [".###.", "#...#", "#..##", "#.#.#", "##..#", "#...#", ".###."],
+        '1' => ["..#..", ".##..", "..#..", "..#..", "..#..", "..#..", ".###."],
+        '2' => [".###.", "#...#", "....#", "...#.", "..#..", ".#...", "#####"],
+        '3' => ["#####", "...#.", "..#..", "...#.", "....#", "#...#", ".###."],
+        '4' => ["...#.", "..##.", ".#.#.", "#..#.", "#####", "...#.", "...#."],
+        '5' => ["#####", "#....", "####.", "....#", "....#", "#...#", ".###."],
+        '6' => ["..##.", ".#...", "#....", "####.", "#...#", "#...#", ".###."],
+        '7' => ["#####", "....#", "...#.", "..#..", ".#...", ".#...", ".#..."],
+        '8' => [".###.", "#...#", "#...#", ".###.", "#...#", "#...#", ".###."],
+        '9' => [".###.", "#...#", "#...#", ".####", "....#", "...#.", ".##.."],
+        'A' => [".###.", "#...#", "#...#", "#####", "#...#", "#...#", "#...#"],
+        'B' => ["####.", "#...#", "#...#", "####.", "#...#", "#...#", "####."],
+        'C' => [".###.", "#...#", "#....", "#....", "#....", "#...#", ".###."],
+        'D' => ["###..", "#..#.", "#...#", "#...#", "#...#", "#..#.", "###.."],
+        'E' => ["#####", "#....", "#....", "####.", "#....", "#....", "#####"],
+        'F' => ["#####", "#....", "#....", "####.", "#....", "#....", "#...."],
+        'G' => [".###.", "#...#", "#....", "#.###", "#...#", "#...#", ".####"],
+        'H' => ["#...#", "#...#", "#...#", "#####", "#...#", "#...#", "#...#"],
+        'I' => [".###.", "..#..", "..#..", "..#..", "..#..", "..#..", ".###."],
+        'J' => ["..###", "...#.", "...#.", "...#.", "...#.", "#..#.", ".##.."],
+        'K' => ["#...#", "#..#.", "#.#..", "##...", "#.#..", "#..#.", "#...#"],
+        'L' => ["#....", "#....", "#....", "#....", "#....", "#....", "#####"],
+        'M' => ["#...#", "##.##", "#.#.#", "#.#.#", "#...#", "#...#", "#...#"],
+        'N' => ["#...#", "#...#", "##..#", "#.#.#", "#..##", "#...#", "#...#"],
+        'O' => [".###.", "#...#", "#...#", "#...#", "#...#", "#...#", ".###."],
+        'P' => ["####.", "#...#", "#...#", "####.", "#....", "#....", "#...."],
+        'Q' => [".###.", "#...#", "#...#", "#...#", "#.#.#", "#..#.", ".##.#"],
+        'R' => ["####.", "#...#", "#...#", "####.", "#.#..", "#..#.", "#...#"],
+        'S' => [".####", "#....", "#....", ".###.", "....#", "....#", "####."],
+        'T' => ["#####", "..#..", "..#..", "..#..", "..#..", "..#..", "..#.."],
+        'U' => ["#...#", "#...#", "#...#", "#...#", "#...#", "#...#", ".###."],
+        'V' => ["#...#", "#...#", "#...#", "#...#", "#...#", ".#.#.", "..#.."],
+        'W' => ["#...#", "#...#", "#...#", "#.#.#", "#.#.#", "#.#.#", ".#.#."],
+        'X' => ["#...#", "#...#", ".#.#.", "..#..", ".#.#.", "#...#", "#...#"],
+        'Y' => ["#...#", "#...#", ".#.#.", "..#..", "..#..", "..#..", "..#.."],
+        'Z' => ["#####", "....#", "...#.", "..#..", ".#...", "#....", "#####"],
+        '-' => [".....", ".....", ".....", "#####", ".....", ".....", "....."],
+        '+' => [".....", "..#..", "..#..", "#####", "..#..", "..#..", "....."],
+        '!' => ["..#..", "..#..", "..#..", "..#..", "..#..", ".....", "..#.."],
+        '?' => [".###.", "#...#", "....#", "...#.", "..#..", ".....", "..#.."],
+        '.' => [".....", ".....", ".....", ".....", ".....", ".##..", ".##.."],
+        ',' => [".....", ".....", ".....", ".....", ".##..", "..#..", ".#..."],
+        '\'' => ["..#..", "..#..", ".#...", ".....", ".....", ".....", "....."],
+        ':' => [".....", ".##..", ".##..", ".....", ".##..", ".##..", "....."],
+        '/' => [".....", "....#", "...#.", "..#..", ".#...", "#....", "....."],
+        '(' => ["...#.", "..#..", ".#...", ".#...", ".#...", "..#..", "...#."],
+        ')' => [".#...", "..#..", "...#.", "...#.", "...#.", "..#..", ".#..."],
+        '&' => [".##..", "#..#.", "#.#..", ".#...", "#.#.#", "#..#.", ".##.#"],
+        '*' => [".....", "..#..", "#.#.#", ".###.", "#.#.#", "..#..", "....."],
+        _ => ["#####", "#...#", "#...#", "#...#", "#...#", "#...#", "#####"],
+    };
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut output = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = (chunk[0] as u32) << 16
+            | (*chunk.get(1).unwrap_or(&0) as u32) << 8
+            | *chunk.get(2).unwrap_or(&0) as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                output.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                output.push('=');
+            }
+        }
+    }
+    return output;
+}
+
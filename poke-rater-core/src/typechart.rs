@@ -0,0 +1,162 @@
+use crate::data::*;
+use crate::pokemon::*;
+use crate::dataset::MULTIPLIERS;
+use std::collections::HashMap;
+use serde::Serialize;
+use strum::IntoEnumIterator;
+
+/// Abilities that change which types hurt a pokemon
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ability {
+    Levitate,
+    EarthEater,
+    FlashFire,
+    WellBakedBody,
+    WaterAbsorb,
+    DrySkin,
+    VoltAbsorb,
+    LightningRod,
+    MotorDrive,
+    SapSipper,
+    ThickFat,
+    WaterBubble,
+    PurifyingSalt,
+    Fluffy,
+    WonderGuard,
+}
+
+impl Ability {
+    /// Multiplier against the pokemon once the ability applies
+    pub fn apply(&self, attack: PTypes, multiplier: f64) -> f64 {
+        use PTypes::*;
+        return match (self, attack) {
+            (Ability::Levitate | Ability::EarthEater, Ground) => 0.0,
+            (Ability::FlashFire | Ability::WellBakedBody, Fire) => 0.0,
+            (Ability::WaterAbsorb | Ability::DrySkin, Water) => 0.0,
+            (Ability::DrySkin, Fire) => multiplier * 1.25,
+            (Ability::VoltAbsorb | Ability::LightningRod | Ability::MotorDrive, Electric) => 0.0,
+            (Ability::SapSipper, Grass) => 0.0,
+            (Ability::ThickFat, Fire | Ice) => multiplier * 0.5,
+            (Ability::WaterBubble, Fire) => multiplier * 0.5,
+            (Ability::PurifyingSalt, Ghost) => multiplier * 0.5,
+            (Ability::Fluffy, Fire) => multiplier * 2.0,
+            (Ability::WonderGuard, _) if multiplier <= 1.0 => 0.0,
+            _ => multiplier
+        };
+    }
+}
+
+// Base forms whose matchups in slides.json assume an ability. Alternate forms may have different abilities
+const ABILITY_OVERRIDES: &[(usize, Ability)] = &[
+    (37, Ability::FlashFire), (38, Ability::FlashFire),
+    (60, Ability::WaterAbsorb), (61, Ability::WaterAbsorb), (62, Ability::WaterAbsorb), (186, Ability::WaterAbsorb),
+    (86, Ability::ThickFat), (87, Ability::ThickFat),
+    (92, Ability::Levitate), (93, Ability::Levitate), (109, Ability::Levitate), (110, Ability::Levitate),
+    (131, Ability::WaterAbsorb), (134, Ability::WaterAbsorb), (135, Ability::VoltAbsorb), (136, Ability::FlashFire),
+    (170, Ability::VoltAbsorb), (171, Ability::VoltAbsorb),
+    (183, Ability::ThickFat), (184, Ability::ThickFat), (298, Ability::ThickFat),
+    (200, Ability::Levitate), (429, Ability::Levitate), (201, Ability::Levitate),
+    (241, Ability::ThickFat), (292, Ability::WonderGuard),
+    (296, Ability::ThickFat), (297, Ability::ThickFat), (325, Ability::ThickFat), (326, Ability::ThickFat),
+    (329, Ability::Levitate), (330, Ability::Levitate), (337, Ability::Levitate), (338, Ability::Levitate),
+    (343, Ability::Levitate), (344, Ability::Levitate), (355, Ability::Levitate), (358, Ability::Levitate), (433, Ability::Levitate),
+    (363, Ability::ThickFat), (364, Ability::ThickFat), (365, Ability::ThickFat),
+    (380, Ability::Levitate), (381, Ability::Levitate), (432, Ability::ThickFat),
+    (436, Ability::Levitate), (437, Ability::Levitate), (455, Ability::Levitate), (466, Ability::MotorDrive),
+    (479, Ability::Levitate), (480, Ability::Levitate), (481, Ability::Levitate), (482, Ability::Levitate),
+    (485, Ability::FlashFire), (488, Ability::Levitate),
+    (522, Ability::LightningRod), (523, Ability::LightningRod), (556, Ability::WaterAbsorb),
+    (592, Ability::WaterAbsorb), (593, Ability::WaterAbsorb),
+    (602, Ability::Levitate), (603, Ability::Levitate), (604, Ability::Levitate),
+    (607, Ability::FlashFire), (608, Ability::FlashFire), (609, Ability::FlashFire),
+    (615, Ability::Levitate), (635, Ability::Levitate),
+    (672, Ability::SapSipper), (673, Ability::SapSipper), (694, Ability::DrySkin), (695, Ability::DrySkin),
+    (704, Ability::SapSipper), (705, Ability::SapSipper), (706, Ability::SapSipper),
+    (721, Ability::WaterAbsorb), (738, Ability::Levitate), (751, Ability::WaterBubble), (752, Ability::WaterBubble),
+    (759, Ability::Fluffy), (760, Ability::Fluffy), (807, Ability::VoltAbsorb), (831, Ability::Fluffy), (832, Ability::Fluffy),
+    (850, Ability::FlashFire), (851, Ability::FlashFire), (871, Ability::LightningRod),
+    (880, Ability::VoltAbsorb), (881, Ability::VoltAbsorb), (882, Ability::WaterAbsorb), (883, Ability::WaterAbsorb),
+    (922, Ability::VoltAbsorb), (923, Ability::VoltAbsorb), (927, Ability::WellBakedBody),
+    (932, Ability::PurifyingSalt), (933, Ability::PurifyingSalt), (934, Ability::PurifyingSalt),
+    (935, Ability::FlashFire), (936, Ability::FlashFire), (937, Ability::FlashFire),
+    (968, Ability::EarthEater), (974, Ability::ThickFat), (975, Ability::ThickFat),
+];
+
+/// Multiplier against a pokemon's typing, the product over its types
+pub fn multiplier(attack: PTypes, typing: &[PTypes]) -> f64 {
+    return typing.iter().map(|x| effectiveness(attack, *x)).product();
+}
+
+/// Best multiplier any of the pokemon's own types (same-type attack bonus moves) gets against defend
+pub fn stab_multiplier(typing: &[PTypes], defend: &[PTypes]) -> f64 {
+    return typing.iter().map(|x| multiplier(*x, defend)).fold(0.0, f64::max);
+}
+
+/// Ability from ABILITY_OVERRIDES, if any
+pub fn ability(pokemon: &Pokemon) -> Option<Ability> {
+    if !pokemon.is_base_form() {
+        return None;
+    }
+    return ABILITY_OVERRIDES.iter().find(|x| x.0 == pokemon.dex_no).map(|x| x.1);
+}
+
+/// Multiplier against the pokemon, including its ability
+pub fn defensive_multiplier(attack: PTypes, pokemon: &Pokemon) -> f64 {
+    let typing = multiplier(attack, &pokemon.typing);
+    return ability(pokemon).map_or(typing, |x| x.apply(attack, typing));
+}
+
+/// Nearest multiplier (%) in MULTIPLIERS, on a log scale so 1.25x is 100 rather than 200 and 1/8x is 25.
+/// Abilities off the dataset's scale then show up in check_matchups instead of being rounded away
+pub fn bucket(multiplier: f64) -> i32 {
+    if multiplier <= 0.0 {
+        return 0;
+    }
+    let distance = |x: &i32| match x {
+        0 => f64::INFINITY,
+        x => (multiplier * 100.0 / *x as f64).log2().abs()
+    };
+    return MULTIPLIERS.iter().copied().min_by(|x, y| distance(x).total_cmp(&distance(y))).unwrap();
+}
+
+/// Matchups in the same form as Pokemon.matchups: multiplier (%) to attacking types, neutral left out.
+/// Every multiplier is one of MULTIPLIERS, see bucket
+pub fn derive_matchups(pokemon: &Pokemon) -> HashMap<i32, Vec<PTypes>> {
+    let mut matchups: HashMap<i32, Vec<PTypes>> = HashMap::new();
+    for attack in PTypes::iter() {
+        let percent = bucket(defensive_multiplier(attack, pokemon));
+        if percent != 100 {
+            matchups.entry(percent).or_default().push(attack);
+        }
+    }
+    return matchups;
+}
+
+/// Attacking type whose scraped multiplier differs from the type chart
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct MatchupMismatch {
+    pub id: PokemonId,
+    pub name: String,
+    pub attack: PTypes,
+    // Multipliers (%)
+    pub scraped: i32,
+    pub computed: i32,
+}
+
+/// Every matchup in the dataset that disagrees with derive_matchups
+pub fn check_matchups(list: &[Pokemon]) -> Vec<MatchupMismatch> {
+    let mut mismatches: Vec<MatchupMismatch> = Vec::new();
+    for pokemon in list {
+        let find = |matchups: &HashMap<i32, Vec<PTypes>>, attack: PTypes| matchups.iter()
+            .find(|x| x.1.contains(&attack))
+            .map_or(100, |x| *x.0);
+        let computed = derive_matchups(pokemon);
+        for attack in PTypes::iter() {
+            let (scraped, computed) = (find(&pokemon.matchups, attack), find(&computed, attack));
+            if scraped != computed {
+                mismatches.push(MatchupMismatch { id: pokemon.id(), name: pokemon.name.clone(), attack, scraped, computed });
+            }
+        }
+    }
+    return mismatches;
+}
@@ -19,6 +19,7 @@ const generationSlideEl = document.getElementById('Generation-Tab');
 const typingSlideEl = document.getElementById('Typing-Tab');
 const matchupSlideEl = document.getElementById('Matchup-Tab')
 const looksSlideEl = document.getElementById("Looks-Tab")
+const categorySlideEl = document.getElementById('Category-Tab')
 let gradeLabels
 // TODO: function used by 2 pages, write in one place and export (DRY)
 function openTab(event, id) {
@@ -49,17 +50,32 @@ async function load() {
         alert('Please load a gradebook')
         window.location.replace('index.html')
     }
-    var data = await invoke('analyze', { numGrades: Number(grades)})
+    var data
+    try {
+        data = await invoke('analyze', { numGrades: Number(grades)})
+    } catch (e) {
+        alert(`Could not analyze: ${e}`)
+        window.location.replace('index.html')
+        return
+    }
     saveDataToJson(data)
 
     renderMediaSlide(data['anime_average'], data['manga_average'])
     renderPerfectSlide(data['perfect_scores'])
     renderWorstSlide(data['worst_scores'])
     renderLooksSlide(data['color_average'])
-    renderGenerationSlide(data['gen_average'])
-    renderTypingSlide(data['dual_type_average'], data['single_type_average'], data['typing_average'])
+    renderGenerationSlide(data['gen_average'], data['gen_distribution'], data['gen_normalised'])
+    renderCategorySlide(data['category_average'], data['category_distribution'], data['category_normalised'])
+    renderTypingSlide(data['dual_type_average'], data['single_type_average'], data['typing_average'], data['typing_distribution'], data['typing_normalised'])
+    renderTypeMatrixSlide(data['type_matrix'])
+    renderCombinationsSlide(data['best_combinations'], data['worst_combinations'])
+    renderEvolutionSlide(data['stage_distribution'], data['stage_normalised'], data['evolution_preference'])
+    renderFamiliesSlide(data['family_consistency'])
     renderMatchupSlide(data['matchup_data'])
+    renderCoverageSlide(data['stab_coverage_data'], data['matchup_summary'])
     renderStatSlide(data['stats_data'])
+    renderDriversSlide(data['feature_importance'])
+    renderSurprisesSlide(data['surprising_grades'])
     document.getElementById('start-tab').click()
 }
 
@@ -217,7 +233,22 @@ function renderLooksSlide(data) {
     }
 }
 
-function renderGenerationSlide(data) {
+// e.g. "n=80, median 4, sd 1.52, percentile 61", grades shown 1-indexed
+function describeDistribution(distribution, score) {
+    var text = `n=${Number(distribution.count)}`
+    if (distribution.median !== null) {
+        text += `, median ${Number(distribution.median) + 1}`
+    }
+    if (distribution.std_dev !== null) {
+        text += `, sd ${distribution.std_dev}`
+    }
+    if (score.percentile !== null) {
+        text += `, percentile ${Math.round(Number(score.percentile))}`
+    }
+    return text
+}
+
+function renderGenerationSlide(data, distributions, scores) {
     generationSlideEl.textContent = ''
     var title = document.createElement('h2')
     title.textContent = 'Generation'
@@ -227,6 +258,7 @@ function renderGenerationSlide(data) {
     var headerEl
     var gen
     var bodyEl
+    var detailEl
     for (var i in data) {
         el = document.createElement('span')
         el.setAttribute('class', 'analysis-container')
@@ -244,11 +276,48 @@ function renderGenerationSlide(data) {
         bodyEl.textContent += ` (${generationNames[gen]})`
         el.appendChild(bodyEl)
 
+        detailEl = document.createElement('small')
+        detailEl.textContent = describeDistribution(distributions[gen], scores[gen])
+        el.appendChild(detailEl)
+
         generationSlideEl.appendChild(el)
     }
 }
 
-function renderTypingSlide(dualTypes, singleTypes, typeAverages) {
+const categoryNames = {
+    Legendary: 'Legendary', Mythical: 'Mythical', Starter: 'Starters', PseudoLegendary: 'Pseudo-legendary',
+    Paradox: 'Paradox', UltraBeast: 'Ultra Beasts', Fossil: 'Fossils', Baby: 'Babies'
+}
+
+// Averages come best first
+function renderCategorySlide(averages, distributions, scores) {
+    var el
+    var headerEl
+    var bodyEl
+    var detailEl
+    var index
+    for (var i in averages) {
+        index = distributions.findIndex((x) => x[0] == averages[i][0])
+        el = document.createElement('span')
+        el.setAttribute('class', 'analysis-container')
+        headerEl = document.createElement('h3')
+        headerEl.textContent = `#${Number(i) + 1}`
+        el.appendChild(headerEl)
+
+        bodyEl = document.createElement('span')
+        bodyEl.setAttribute('class', 'analysis-body')
+        bodyEl.textContent = `${categoryNames[averages[i][0]]}: ${(Number(averages[i][1]) + 1).toFixed(2)}`
+        el.appendChild(bodyEl)
+
+        detailEl = document.createElement('small')
+        detailEl.textContent = describeDistribution(distributions[index][1], scores[index][1])
+        el.appendChild(detailEl)
+
+        categorySlideEl.appendChild(el)
+    }
+}
+
+function renderTypingSlide(dualTypes, singleTypes, typeAverages, typeDistributions, typeScores) {
     var numTypesEl = document.getElementById('Num-Types')
     dualTypes = Number(dualTypes)
     singleTypes = Number(singleTypes)
@@ -269,6 +338,7 @@ function renderTypingSlide(dualTypes, singleTypes, typeAverages) {
 
     var typeName
     var headerEl
+    var detailEl
     var colIndex
     for (var i in typeAverages) {
         el = document.createElement('span')
@@ -285,6 +355,11 @@ function renderTypingSlide(dualTypes, singleTypes, typeAverages) {
 
         el.appendChild(bodyEl)
 
+        detailEl = document.createElement('small')
+        detailEl.textContent = describeDistribution(typeDistributions.find((x) => x[0] == typeName)[1],
+            typeScores.find((x) => x[0] == typeName)[1])
+        el.appendChild(detailEl)
+
         colIndex = parseInt(Number(i) / 6); 
         columns[colIndex].appendChild(el)
     }
@@ -304,13 +379,15 @@ function buildTable(tableEl, data, invertColors=false) {
         minColor = '#007f00'
     }
 
-    var numColumns = Object.keys(data[0]).length
+    // Every row follows the header's column order, maps from the backend do not keep one
+    var columns = Object.keys(data[0])
+    var numColumns = columns.length
     var columnMaxValues = Array.from('0'.repeat(numColumns))
     var columnMinValues = Array.apply(null, Array(numColumns)).map(function () { return Infinity; })
     var index = 0
     for (var grade in data) {
         index = 0
-        for (var column in data[grade]) {
+        for (var column of columns) {
             columnMaxValues[index] = Math.max(columnMaxValues[index], data[grade][column])
             columnMinValues[index] = Math.min(columnMinValues[index], data[grade][column])
             index += 1
@@ -331,7 +408,7 @@ function buildTable(tableEl, data, invertColors=false) {
         cell.textContent = Number(grade) + 1
         row.appendChild(cell)
         index = 0
-        for (var column in data[grade]) {
+        for (var column of columns) {
             value = parseFloat(data[grade][column])
             cell = document.createElement('td')
             cell.textContent = value
@@ -352,6 +429,32 @@ function buildTable(tableEl, data, invertColors=false) {
 
 
 }
+// Offense: share of pokemon per grade whose own types hit each type super effectively
+function renderCoverageSlide(coverage, summary) {
+    var tableBody = document.getElementById('Coverage-Table')
+    var titleRowEl = document.getElementById('Coverage-Tab-Header-Row')
+    var cell
+    for (var type of Object.keys(coverage[0])) {
+        cell = document.createElement('th')
+        cell.textContent = type
+        titleRowEl.appendChild(cell)
+    }
+    buildTable(tableBody, coverage)
+
+    var names = {
+        weaknesses: 'Weaknesses', resistances: 'Resistances', immunities: 'Immunities',
+        super_effective: 'Hits super effectively', walled_by: 'Walled by'
+    }
+    titleRowEl = document.getElementById('Matchup-Summary-Header-Row')
+    for (var key in names) {
+        cell = document.createElement('th')
+        cell.textContent = names[key]
+        titleRowEl.appendChild(cell)
+    }
+    var rows = summary.map(x => Object.fromEntries(Object.keys(names).map(key => [key, x[key]])))
+    buildTable(document.getElementById('Matchup-Summary-Table'), rows)
+}
+
 function renderMatchupSlide(data) {
     var titleRowEl = document.getElementById("Matchup-Tab-Header-Row")
     var tableBody = document.getElementById("Matchup-Table")
@@ -415,4 +518,199 @@ function renderStatSlide(data) {
     //         row.appendChild(cell)
     //     }
     // }
-}
\ No newline at end of file
+}
+
+// e.g. { kind: 'Typing', value: 'Fire' } -> 'Fire type'
+function featureName(feature) {
+    switch (feature.kind) {
+        case 'Typing': return `${feature.value} type`
+        case 'Gen': return `Generation ${Number(feature.value)} (${generationNames[Number(feature.value) - 1]})`
+        case 'Color': return `${feature.value} color`
+        case 'Stat': return feature.value
+        case 'StatTotal': return 'Base stat total'
+        case 'Anime': return 'Anime appearances'
+        case 'Manga': return 'Manga appearances'
+        case 'DualType': return 'Dual type'
+    }
+    return feature.kind
+}
+
+// Heatmap from red (lowest average) to green (highest), empty cells for typings with no rated pokemon
+function renderTypeMatrixSlide(matrix) {
+    var tableEl = document.getElementById('TypeMatrix-Table')
+    var values = matrix.averages.flat().filter(x => x !== null).map(Number)
+    var min = Math.min(...values)
+    var max = Math.max(...values)
+
+    var rowEl = document.createElement('tr')
+    rowEl.appendChild(document.createElement('th'))
+    for (var typing of matrix.types) {
+        var headerEl = document.createElement('th')
+        headerEl.textContent = typing.slice(0, 3)
+        headerEl.title = typing
+        rowEl.appendChild(headerEl)
+    }
+    tableEl.appendChild(rowEl)
+
+    var cellEl
+    var value
+    var share
+    for (var i in matrix.types) {
+        rowEl = document.createElement('tr')
+        cellEl = document.createElement('th')
+        cellEl.textContent = matrix.types[i]
+        rowEl.appendChild(cellEl)
+        for (var j in matrix.types) {
+            cellEl = document.createElement('td')
+            if (matrix.averages[i][j] !== null) {
+                value = Number(matrix.averages[i][j])
+                share = max > min ? (value - min) / (max - min) : 1
+                cellEl.textContent = (value + 1).toFixed(1)
+                cellEl.title = `${matrix.types[i]}${i == j ? '' : '/' + matrix.types[j]}: ${Number(matrix.counts[i][j])} rated`
+                cellEl.setAttribute('style', `background-color: rgb(${Math.round(240 * (1 - share))}, ${Math.round(200 * share)}, 64);`)
+            }
+            rowEl.appendChild(cellEl)
+        }
+        tableEl.appendChild(rowEl)
+    }
+}
+
+function renderDriversSlide(data) {
+    var info = document.getElementById('Drivers-Tab-Info')
+    if (!data) {
+        info.textContent = 'Rate more pokemon to see what drives your grades'
+        return
+    }
+    info.textContent = `Change in grade from each factor with everything else held equal. ` + 
+        `Numbers are per standard deviation for stats and appearances. ` +
+        `Explains ${(Number(data.r_squared) * 100).toFixed(0)}% of your grades.`
+
+    var tableEl = document.getElementById('Drivers-Table')
+    var factors = data.factors
+    // Strongest 8 each way
+    var shown = factors.length > 16 ? factors.slice(0, 8).concat(factors.slice(-8)) : factors
+    var rowEl
+    var cellEl
+    for (var factor of shown) {
+        rowEl = document.createElement('tr')
+        cellEl = document.createElement('td')
+        cellEl.textContent = featureName(factor.feature)
+        rowEl.appendChild(cellEl)
+
+        cellEl = document.createElement('td')
+        cellEl.textContent = (Number(factor.effect) > 0 ? '+' : '') + factor.effect
+        cellEl.setAttribute('style', `color: ${Number(factor.effect) > 0 ? '#007f00' : '#7f0000'};`)
+        rowEl.appendChild(cellEl)
+        tableEl.appendChild(rowEl)
+    }
+}
+
+// e.g. "Poison types from Hoenn"
+function renderCombinationsSlide(best, worst) {
+    document.getElementById('Combinations-Tab-Info').textContent =
+        'Generation and type or color combinations with at least 5 rated pokemon, compared to your average grade.'
+    var tables = [
+        [document.getElementById('Combinations-Best-Table'), best],
+        [document.getElementById('Combinations-Worst-Table'), worst]
+    ]
+    var rowEl
+    var cellEl
+    var name
+    for (var [tableEl, combinations] of tables) {
+        for (var combination of combinations) {
+            name = `${featureName(combination.feature)}s from ${generationNames[Number(combination.gen_no) - 1]}`
+            rowEl = document.createElement('tr')
+            for (var text of [name, (Number(combination.average) + 1).toFixed(2), Number(combination.count)]) {
+                cellEl = document.createElement('td')
+                cellEl.textContent = text
+                rowEl.appendChild(cellEl)
+            }
+            tableEl.appendChild(rowEl)
+        }
+    }
+}
+
+const stageNames = { Basic: 'Basic', Stage1: 'Stage 1', Stage2: 'Stage 2' }
+
+function renderEvolutionSlide(distributions, scores, preference) {
+    var families = Number(preference.families)
+    var text = `Within ${families} families you preferred the final evolution in ${Number(preference.prefer_final)}, `
+    text += `the first stage in ${Number(preference.prefer_basic)} and rated them the same in ${Number(preference.ties)}.`
+    if (families > 0) {
+        var difference = Number(preference.mean_difference)
+        text += difference >= 0 ? ` Final evolutions average ${difference.toFixed(2)} grades higher.`
+            : ` First stages average ${(-difference).toFixed(2)} grades higher.`
+    }
+    document.getElementById('Evolution-Tab-Info').textContent = text
+
+    var tableEl = document.getElementById('Evolution-Table')
+    var rowEl
+    var cellEl
+    var deviation
+    for (var i in distributions) {
+        deviation = preference.stage_deviation[i][1]
+        rowEl = document.createElement('tr')
+        for (var value of [
+            stageNames[distributions[i][0]],
+            distributions[i][1].mean === null ? '-' : (Number(distributions[i][1].mean) + 1).toFixed(2),
+            deviation === null ? '-' : (Number(deviation) >= 0 ? '+' : '') + Number(deviation).toFixed(2),
+            describeDistribution(distributions[i][1], scores[i][1])
+        ]) {
+            cellEl = document.createElement('td')
+            cellEl.textContent = value
+            rowEl.appendChild(cellEl)
+        }
+        tableEl.appendChild(rowEl)
+    }
+}
+
+function renderFamiliesSlide(data) {
+    var families = Number(data.families)
+    var text = `You rated ${data.uniform.length} of ${families} families the same all the way through.`
+    if (data.mean_trend !== null) {
+        var trend = Number(data.mean_trend)
+        text += ` On average grades ${trend >= 0 ? 'rise' : 'fall'} by ${Math.abs(trend).toFixed(2)} from first to last stage.`
+    }
+    document.getElementById('Families-Tab-Info').textContent = text
+
+    var tableEl = document.getElementById('Families-Table')
+    var rowEl
+    var cellEl
+    for (var family of data.widest) {
+        rowEl = document.createElement('tr')
+        for (var value of [
+            family.name,
+            family.members.map((x) => `${x[0]} (${gradeLabels[Number(x[1]) - 1]})`).join(', '),
+            Number(family.spread)
+        ]) {
+            cellEl = document.createElement('td')
+            cellEl.textContent = value
+            rowEl.appendChild(cellEl)
+        }
+        tableEl.appendChild(rowEl)
+    }
+}
+
+function renderSurprisesSlide(data) {
+    if (!data) {
+        document.getElementById('Surprises-Tab-Info').textContent = 'Rate more pokemon to see surprising grades'
+        return
+    }
+    var tables = [
+        [document.getElementById('Surprises-Higher-Table'), data.higher],
+        [document.getElementById('Surprises-Lower-Table'), data.lower]
+    ]
+    var rowEl
+    var cellEl
+    for (var [tableEl, surprises] of tables) {
+        for (var surprise of surprises) {
+            rowEl = document.createElement('tr')
+            for (var text of [surprise.name, gradeLabels[Number(surprise.grade) - 1], Number(surprise.predicted).toFixed(1)]) {
+                cellEl = document.createElement('td')
+                cellEl.textContent = text
+                rowEl.appendChild(cellEl)
+            }
+            tableEl.appendChild(rowEl)
+        }
+    }
+}
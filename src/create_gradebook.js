@@ -107,6 +107,7 @@ async function openGradingPage() {
     }
     window.localStorage.setItem('fileName', fileNameEl.value)
     window.localStorage.setItem('gradeLabels', gradeLabels.toString())
+    window.localStorage.setItem('newGradebook', 'true')
 
     window.location.replace('grading.html')
 }
@@ -1,5 +1,5 @@
 const { invoke } = window.__TAURI__.tauri
-const { writeTextFile, readTextFile, exists, BaseDirectory }= window.__TAURI__.fs
+const { exists, BaseDirectory }= window.__TAURI__.fs
 const { dataDir } = window.__TAURI__.path
 
 const maxGen = 9
@@ -18,8 +18,8 @@ const gradeLabelsDisplayEl = document.getElementById('GradeLabelDisplay')
 */
 let fileName = fileNameInputEl.value
 async function setFileName(name) {
-  if (await exists(`${name}.csv`, { dir: BaseDirectory.AppLocalData })) {
-    var doOverwrite = await confirm(`'${name}.csv' already exists. Would you like to overwrite it?`)
+  if (await exists(`${name}.gradebook.json`, { dir: BaseDirectory.AppLocalData })) {
+    var doOverwrite = await confirm(`'${name}' already exists. Would you like to overwrite it?`)
 
     if (!doOverwrite) {
       name = fileName
@@ -36,7 +36,11 @@ document.addEventListener('keydown', function (event) {
   if (slideContainerEl.style.display == 'hidden') {
     return
   }
-  if (event.key == 'ArrowLeft') {
+  if (event.ctrlKey && (event.key == 'z' || event.key == 'y')) {
+    event.preventDefault()
+    event.key == 'z' ? undo() : redo()
+  }
+  else if (event.key == 'ArrowLeft') {
     event.preventDefault()
     prevSlide()
   }
@@ -74,6 +78,7 @@ let currentPokemonGroup
 let maxGrade = 5
 let gradeLabels = [ 1, 2, 3, 4, 5 ]
 let typesList
+let categoriesList
 let showGradeLabelDisplay = true
 
 // First number entered is applied to other related
@@ -102,11 +107,15 @@ function openTab(event, id) {
 }
 
 async function load() {
-  var data = await fetch('./slides.json')
-    .then(data => data.json())
-
-  // data = await data.json()
-  slides = await invoke('init_list', { slides: data })
+  // Backend loads and validates slides.json itself
+  try {
+    slides = await invoke('init_list')
+  } catch (report) {
+    console.error(report)
+    alert(`Could not load pokemon data (${report.errors.length} errors):\n` + 
+      report.errors.slice(0, 10).map((x) => JSON.stringify(x)).join('\n'))
+    return
+  }
   init()
 }
 
@@ -129,6 +138,11 @@ async function init() {
     gradeLabels = gradeLabelSaved.split(',')
     maxGrade = gradeLabels.length
   }
+  // Set by create-gradebook.html
+  if (window.localStorage.getItem('newGradebook')) {
+    window.localStorage.removeItem('newGradebook')
+    await invoke('new_gradebook', { gradeLabels: gradeLabels.map(String) })
+  }
   renderGradeLabelsDisplay()
 
   // Autofill screen
@@ -137,39 +151,34 @@ async function init() {
   var opt
   for (var i = 0; i < maxGrade; i++) {
     opt = document.createElement('option')
-    opt.setAttribute('value', i)
+    // Grades aren't 0-index
+    opt.setAttribute('value', i + 1)
     opt.textContent = gradeLabels[i]
     autoFillGradesEl.appendChild(opt)
   }
 
   typesList = await invoke('list_ptypes')
+  categoriesList = await invoke('list_categories')
 
   document.getElementById('start-tab').click()
   nextSlide()
 }
-// Get csv of grades in dex order
+// Load gradebook (legacy .csv files are migrated by the backend)
 async function read(fileName) {
-  console.log(`Reading ${fileName}.csv`)
+  console.log(`Reading ${fileName}`)
   
-  var contents = await readTextFile(`${fileName}.csv`, { dir: BaseDirectory.AppLocalData }).then((text) => text)
-  contents = contents.split('\n')
+  var gradebook = await invoke('load_gradebook', { name: fileName })
 
   window.localStorage.setItem('fileName', fileName)
   window.localStorage.removeItem('filePath')
   
-  var gradeCsv
-  if (contents.length == 1) {
-    gradeCsv = contents[0]
-  }
-  else {
-    gradeLabels = contents[0].split(',')
-    window.localStorage.setItem('gradeLabels', gradeLabels.toString())
-    gradeCsv = contents.slice(1).toString()
-  }
-  var startSlide = await invoke('parse_csv_file', { csv: gradeCsv })
+  gradeLabels = gradebook.grade_labels
+  window.localStorage.setItem('gradeLabels', gradeLabels.toString())
+
+  var startSlide = gradebook.cursor
   for (var i in slides) {
     
-    if (slides[i].includes(startSlide)) {
+    if (slides[i].some((id) => isSameId(id, startSlide))) {
       slideIndex = i - 1
       break
     }
@@ -177,6 +186,18 @@ async function read(fileName) {
 
 }
 
+// Pokemon are identified by dex_no and form (null for base form)
+function isSameId(a, b) {
+  return a != null && b != null && a.dex_no == b.dex_no && a.form == b.form
+}
+
+function picturePath(pokemon) {
+  if (pokemon['form']) {
+    return `./assets/pics/${pokemon['dex_no']}-${pokemon['form']}.jpg`
+  }
+  return `./assets/pics/${pokemon['dex_no']}.jpg`
+}
+
 // Draw grade labels for non-numeric grades
 function renderGradeLabelsDisplay() {
   var isDigit = gradeLabels.find((value) => value.match(/^[0-9]+$/) == null)
@@ -226,46 +247,59 @@ function autoFillRuleSelected(obj, id) {
       valueContainerEl.appendChild(opt)
     }
   }
+  else if (obj.value == 'category') {
+    for (var category of categoriesList) {
+      opt = document.createElement('option')
+      opt.text = category
+      opt.setAttribute('value', category)
+      valueContainerEl.appendChild(opt)
+    }
+  }
+}
+
+// Selected rule as text, e.g. type:Fire
+function autoFillRuleText(rule, val) {
+  if (rule == 'type') {
+    return 'type:' + val.replace(' Type', '')
+  } else if (rule == 'gen') {
+    return 'gen:' + val.replace('Generation ', '')
+  } else if (rule == 'category') {
+    return 'category:' + val
+  }
+  return null
 }
 
 // Create new autofill rule element
-function addAutoFillRule(rule1, rule2, val1, val2, useRule2, grade, priority) {
-  if (!val1 && !val2) {
+// expression (e.g. gen:1 and (type:Fire or type:Dragon)) is used instead of the dropdowns if given
+async function addAutoFillRule(rule1, rule2, val1, val2, useRule2, expression, grade, priority, mode) {
+  var text = expression.trim()
+  if (!text) {
+    var parts = [ autoFillRuleText(rule1, val1) ]
+    if (useRule2 || !parts[0]) {
+      parts.push(autoFillRuleText(rule2, val2))
+    }
+    text = parts.filter(x => x).join(' and ')
+  }
+  if (!text) {
+    return
+  }
+  try {
+    // Normalised back to text so the list shows how the rule was understood
+    text = await invoke('parse_rule', { text: text })
+  } catch (e) {
+    alert(`Invalid rule: ${e}`)
     return
   }
+
   var ruleContainer = document.createElement('span')
   var rule = {
-    type_rule1: null,
-    type_rule2: null,
-    gen_rule1: null,
-    gen_rule2: null,
+    rule: text,
     grade: Number(grade),
-    priority: Number(priority)
-  }
-
-  if (val1) {
-    ruleContainer.textContent += val1
-    if (rule1 == 'type') {
-      rule.type_rule1 = val1.replace(' Type', '')
-    } else if (rule1 == 'gen') {
-      rule.gen_rule1 = Number(val1.replace('Generation ', ''))
-    }
-  } 
-  if (useRule2 && val1 && val2) {
-    ruleContainer.textContent += ' && '
-  }
-  if ((useRule2 || !val1) && val2) {
-    ruleContainer.textContent += val1
-    
-    if (rule2 == 'type') {
-      var val = val2.replace(' Type', '')
-      rule.type_rule2 = val
-    } else if (rule2 == 'gen') {
-      rule.gen_rule2 = Number(val2.replace('Generation ', ''))
-    }
-
+    priority: Number(priority),
+    mode: mode
   }
-  ruleContainer.textContent += ` = Grade: ${gradeLabels[rule.grade]} | Priority: ${rule.priority}`
+  ruleContainer.textContent += text
+  ruleContainer.textContent += ` = Grade: ${gradeLabels[rule.grade - 1]} | Priority: ${rule.priority} | ${mode}`
   var deleteButtonEl = document.createElement('button')
   deleteButtonEl.setAttribute('onclick', 'removeAutoFillRule(this)')
   deleteButtonEl.textContent = 'Delete'
@@ -288,11 +322,10 @@ async function removeAutoFillRule(el) {
 
 // Apply rules to pokemon list
 async function applyAutoFillRules() {
-  // Sort in ascending order based on priority
-  autoFillRules.sort((a, b) => Number(a.priority) - Number(b.priority))
-
-  await invoke('autofill', { rules: autoFillRules })
-  alert('Applied autofill rules')
+  // Priority is handled by the backend
+  var report = await invoke('autofill', { rules: autoFillRules })
+  var perRule = autoFillRules.map((rule, i) => `${rule.rule}: ${report.changed_per_rule[i]}`)
+  alert(`Applied autofill rules, ${report.changed} grades changed\n${perRule.join('\n')}`)
 
   // Apply new grades to current slide (reload slide)
   slideIndex -= 1
@@ -300,16 +333,37 @@ async function applyAutoFillRules() {
 
 }
 
+// Show grades the rules would change without applying them
+async function previewAutoFillRules() {
+  var changes = await invoke('preview_autofill', { rules: autoFillRules })
+  var label = grade => grade ? gradeLabels[grade - 1] : '-'
+  var lines = changes.map(x => `${x.name}: ${label(x.old)} -> ${label(x.new)} (${autoFillRules[x.rule].rule})`)
+  alert(`${changes.length} grades would change\n${lines.join('\n')}`)
+}
+
+// Restore grades from before the last Apply
+async function undoAutoFillRules() {
+  try {
+    var restored = await invoke('undo_autofill')
+    alert(`Restored ${restored} grades`)
+  } catch (e) {
+    alert(e)
+    return
+  }
+  slideIndex -= 1
+  nextSlide()
+}
+
 // Create element holding pokemon
 function _addPokemonToSlide(pokemon, index) {
-  console.log('Opened ' + pokemon['name'] + ' (' + pokemon['dex_no'] + ')')
+  console.log('Opened ' + pokemon['name'] + ' (' + pokemon['dex_no'] + (pokemon['form'] ? '-' + pokemon['form'] : '') + ')')
   doAutoFill = true
 
   var slide = document.createElement('div')
   slide.className = 'slide'
 
   var img = document.createElement('img')
-  img.setAttribute('src', './assets/pics/' + pokemon['dex_no'] + '.jpg')
+  img.setAttribute('src', picturePath(pokemon))
   slide.appendChild(img)
 
   var opts = document.createElement('select')
@@ -319,7 +373,12 @@ function _addPokemonToSlide(pokemon, index) {
   opts.setAttribute('tabindex', Number(index) + 3)
 
 
-  var opt
+  // Unrated
+  var opt = document.createElement('option')
+  opt.setAttribute('value', '')
+  opt.text = '-'
+  opts.appendChild(opt)
+
   for (var i = 0; i < maxGrade; i++) {
     opt = document.createElement('option')
     // Grades aren't 0-index
@@ -330,9 +389,7 @@ function _addPokemonToSlide(pokemon, index) {
 
   slide.appendChild(opts)
 
-  if ('grade' in pokemon) {
-    opts.value = pokemon.grade
-  } 
+  opts.value = pokemon.grade ?? ''
   opts.addEventListener('keyup', function (event) {
     if (!doAutoFill && event.key >= '0' && event.key <= '9') {
       var grade = Number(event.key)
@@ -359,7 +416,7 @@ async function nextSlide() {
   var pokemon
   for (var index in currentPokemonGroup) {
 
-    pokemon = await invoke('get_pokemon_at', {dexNo: Number(currentPokemonGroup[index])})
+    pokemon = await invoke('get_pokemon_at', { id: currentPokemonGroup[index] })
     slide = _addPokemonToSlide(pokemon, index)
     slideContainerEl.appendChild(slide)
   }
@@ -382,7 +439,7 @@ async function prevSlide() {
   var pokemon
   for (var index in currentPokemonGroup) {
 
-    pokemon = await invoke('get_pokemon_at', { dexNo: Number(currentPokemonGroup[index]) })
+    pokemon = await invoke('get_pokemon_at', { id: currentPokemonGroup[index] })
     slide = _addPokemonToSlide(pokemon, index)
     slideContainerEl.appendChild(slide)
   }
@@ -391,10 +448,167 @@ async function prevSlide() {
 
 // Apply grade to pokemon
 async function setGrade(event, index) {
-  var num = Number(currentPokemonGroup[index])
-  var value = Number(event.currentTarget.value)
-  console.log('Set ' + num + ' to ' + value)
-  await invoke('set_grade', { dexNo: num, grade: value })
+  var id = currentPokemonGroup[index]
+  // Empty value (or 0 key) is unrated
+  var value = Number(event.currentTarget.value) || null
+  console.log('Set ' + id.dex_no + (id.form ? '-' + id.form : '') + ' to ' + value)
+  await invoke('set_grade', { id: id, grade: value })
+  // Saved right away so the edit history survives restarts
+  writeToFs()
+}
+
+// Revert the last grade change (Ctrl+Z)
+async function undo() {
+  try {
+    await invoke('undo')
+  } catch (e) {
+    console.log(e)
+    return
+  }
+  writeToFs()
+  slideIndex -= 1
+  nextSlide()
+}
+
+// Apply the last undone grade change again (Ctrl+Y)
+async function redo() {
+  try {
+    await invoke('redo')
+  } catch (e) {
+    console.log(e)
+    return
+  }
+  writeToFs()
+  slideIndex -= 1
+  nextSlide()
+}
+
+async function showHistory() {
+  var history = await invoke('history')
+  var label = grade => grade ? gradeLabels[grade - 1] : '-'
+  var lines = history.slice(0, 20).map(entry => {
+    var time = new Date(entry.time * 1000).toLocaleTimeString()
+    var changes = entry.changes.slice(0, 3)
+      .map(x => `${x.dex_no}${x.form ? '-' + x.form : ''}: ${label(x.previous)} -> ${label(x.grade)}`)
+    var more = entry.changes.length > 3 ? ` (+${entry.changes.length - 3} more)` : ''
+    return `${time} ${entry.source}: ${changes.join(', ')}${more}`
+  })
+  alert(lines.length ? lines.join('\n') : 'No grade changes yet')
+}
+
+// Jump to first slide with an unrated pokemon
+async function nextUnrated() {
+  var unrated = await invoke('list_unrated')
+  if (unrated.length == 0) {
+    alert('Every pokemon has been rated')
+    return
+  }
+  for (var i in slides) {
+    if (slides[i].some((id) => isSameId(id, unrated[0]))) {
+      slideIndex = i - 1
+      break
+    }
+  }
+  nextSlide()
+}
+
+// Pairwise mode: click the pokemon you prefer
+let pairwiseCount = 0
+async function nextPair() {
+  var containerEl = document.getElementById('PairwiseContainer')
+  var pair = await invoke('next_pair')
+  containerEl.innerHTML = ''
+  if (!pair) {
+    return
+  }
+
+  var slide
+  var img
+  for (var i = 0; i < 2; i++) {
+    slide = document.createElement('div')
+    slide.className = 'slide'
+    img = document.createElement('img')
+    img.setAttribute('src', picturePath(pair[i]))
+    img.setAttribute('title', pair[i]['name'])
+    slide.appendChild(img)
+    slide.addEventListener('click', choosePair.bind(null, pair[i], pair[1 - i]))
+    containerEl.appendChild(slide)
+  }
+}
+
+async function choosePair(winner, loser) {
+  var id = (pokemon) => ({ dex_no: pokemon['dex_no'], form: pokemon['form'] })
+  await invoke('record_preference', { winner: id(winner), loser: id(loser) })
+  pairwiseCount += 1
+  document.getElementById('PairwiseCount').textContent = `${pairwiseCount} picks this session`
+  nextPair()
+}
+
+async function applyPairwiseGrades() {
+  var count = await invoke('apply_pairwise_grades')
+  alert(`Graded ${count} pokemon from your picks`)
+  slideIndex -= 1
+  nextSlide()
+}
+
+async function exportTierList() {
+  var paths = await invoke('export_tier_list', { name: fileName, gradeLabels: gradeLabels.map(String) })
+  alert(`Saved tier list:\n${paths.join('\n')}`)
+}
+
+// Converts every grade to the new labels, then reloads with them
+async function rescaleGradebook() {
+  var labels = document.getElementById('RescaleLabels').value
+    .split(',').map(x => x.trim()).filter(x => x != '')
+  if (labels.length < 2 || !confirm(`Convert grades from ${maxGrade} to ${labels.length} grades? This clears undo history`)) {
+    return
+  }
+  var gradebook = await invoke('rescale_gradebook', { gradeLabels: labels })
+  gradeLabels = gradebook.grade_labels
+  maxGrade = gradeLabels.length
+  window.localStorage.setItem('gradeLabels', gradeLabels.toString())
+  await writeToFs()
+  window.location.reload()
+}
+
+// Compares the saved copy of this gradebook, so save first
+async function compareGradebooks() {
+  var other = document.getElementById('CompareWith').value
+  await writeToFs()
+  try {
+    var comparison = await invoke('compare_gradebooks', { a: fileName, b: other })
+    // NaN is sent as null
+    var spearman = comparison.overall.spearman == null ? '-' : comparison.overall.spearman.toFixed(2)
+    var disagreements = comparison.disagreements
+      .map(x => `${x.name}: ${x.label_a} vs ${x.label_b}`)
+    alert(`Rated in both: ${comparison.count}\n` +
+      `Rank correlation: ${spearman}\n` +
+      `Biggest disagreements:\n${disagreements.join('\n')}`)
+  } catch (e) {
+    alert(e)
+  }
+}
+
+// Ranks saved gradebooks together and exports the consensus tier list
+async function groupConsensus() {
+  var names = document.getElementById('ConsensusNames').value
+    .split(',').map(x => x.trim()).filter(x => x != '')
+  var args = {
+    names: names,
+    normalisation: document.getElementById('ConsensusNormalisation').value,
+    gradeLabels: gradeLabels.map(String)
+  }
+  await writeToFs()
+  try {
+    var consensus = await invoke('group_consensus', args)
+    var paths = await invoke('export_consensus_tier_list', args)
+    var raters = consensus.raters
+      .map(x => `${x.name}: most contrarian on ${x.contrarian.map(c => c.name).join(', ')}`)
+    alert(`Most controversial: ${consensus.controversial.map(x => x.name).join(', ')}\n\n` +
+      `${raters.join('\n')}\n\nSaved tier list:\n${paths.join('\n')}`)
+  } catch (e) {
+    alert(e)
+  }
 }
 
 function startAnalysis() {
@@ -410,9 +624,10 @@ async function saveGradebook() {
 }
 
 async function writeToFs() {
-  var gradebook = await invoke('get_gradebook_csv', { slideIndex: currentPokemonGroup[0] })
-  var labels = gradeLabels.toString()
-
-  await writeTextFile(`${fileName}.csv`, `${labels}\n${gradebook}`, { dir: BaseDirectory.AppLocalData })
+  await invoke('save_gradebook', { 
+    name: fileName, 
+    gradeLabels: gradeLabels.map(String), 
+    cursor: currentPokemonGroup[0] 
+  })
   console.log(`Saved '${fileName}'`)
 }
\ No newline at end of file
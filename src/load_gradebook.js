@@ -19,8 +19,12 @@ async function listCsvFiles() {
     var el
     var index = 1
     for (var file of files) {
-        if (file.name.endsWith('.csv')) {
-            file = file.name.replace('.csv', '')
+        // .csv files are from older versions, and are converted when opened
+        if (file.name.endsWith('.gradebook.json') || file.name.endsWith('.csv')) {
+            file = file.name.replace('.gradebook.json', '').replace('.csv', '')
+            if (csvFiles.includes(file)) {
+                continue
+            }
             csvFiles.push(file)
             el = document.createElement('button')
             el.setAttribute('tabindex', index)